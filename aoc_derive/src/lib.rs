@@ -0,0 +1,125 @@
+//! Derive macros for Advent of Code solvers
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::HashMap;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitByte, LitChar};
+
+/// Derive `TryFrom<char>`, `TryFrom<u8>` and `Display` for a fieldless enum.
+///
+/// Each variant is mapped to a char with `#[char('x')]`. A variant that shouldn't be parsed, but
+/// still needs to be displayed, can use `#[display('x')]` instead.
+///
+/// ```ignore
+/// #[derive(CharEnum)]
+/// enum Tile {
+///     #[char('.')]
+///     Empty,
+///     #[char('#')]
+///     Wall,
+///     #[display('O')]
+///     Visited,
+/// }
+/// ```
+///
+/// The conversions return the offending char or byte as the error. Only ASCII chars can be parsed
+/// from a `u8`.
+#[proc_macro_derive(CharEnum, attributes(char, display))]
+pub fn derive_char_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    char_enum(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn char_enum(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "CharEnum can only be derived for enums",
+        ));
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut char_arms = vec![];
+    let mut byte_arms = vec![];
+    let mut display_arms = vec![];
+    let mut seen = HashMap::new();
+
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "CharEnum variants can't have fields",
+            ));
+        }
+
+        let mut parse_char: Option<LitChar> = None;
+        let mut display_char: Option<LitChar> = None;
+        for attr in &variant.attrs {
+            if attr.path().is_ident("char") {
+                parse_char = Some(attr.parse_args()?);
+            } else if attr.path().is_ident("display") {
+                display_char = Some(attr.parse_args()?);
+            }
+        }
+
+        if let Some(c) = &parse_char {
+            if let Some(other) = seen.insert(c.value(), ident) {
+                let msg = format!("{:?} is already used by {other}", c.value());
+                return Err(Error::new_spanned(c, msg));
+            }
+
+            char_arms.push(quote! { #c => Ok(#name::#ident) });
+            if c.value().is_ascii() {
+                let b = LitByte::new(c.value() as u8, c.span());
+                byte_arms.push(quote! { #b => Ok(#name::#ident) });
+            }
+        }
+
+        let Some(display_char) = display_char.or(parse_char) else {
+            return Err(Error::new_spanned(
+                variant,
+                "missing #[char(..)] or #[display(..)] attribute",
+            ));
+        };
+        display_arms.push(quote! { #name::#ident => #display_char });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::TryFrom<char> for #name #ty_generics #where_clause {
+            type Error = char;
+
+            fn try_from(c: char) -> ::core::result::Result<Self, char> {
+                match c {
+                    #(#char_arms,)*
+                    _ => Err(c),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<u8> for #name #ty_generics #where_clause {
+            type Error = u8;
+
+            fn try_from(b: u8) -> ::core::result::Result<Self, u8> {
+                match b {
+                    #(#byte_arms,)*
+                    _ => Err(b),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let c = match self {
+                    #(#display_arms,)*
+                };
+                ::core::fmt::Write::write_char(f, c)
+            }
+        }
+    })
+}
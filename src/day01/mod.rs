@@ -1,85 +1,398 @@
+use crate::util::{
+    aho_corasick::{AhoCorasick, Match},
+    checked::{self, CheckedIter, CheckedOps},
+    options,
+};
+use lazy_static::lazy_static;
+use std::fmt::{self, Display};
+use std::fs;
+use std::ops::Range;
+
 pub fn parse(input: &str) -> impl Iterator<Item = &str> {
     // the best parse function
     input.lines()
 }
 
-const DIGIT_NAMES: &[&str] = &[
-    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+/// Built-in vocabularies for [Vocabulary::builtin]. The words of each language map to the digits
+/// 0 through 9 in order, and a digit may have more than one word.
+const LANGUAGES: &[(&str, &[&[&str]])] = &[
+    (
+        "english",
+        &[
+            &["zero"],
+            &["one"],
+            &["two"],
+            &["three"],
+            &["four"],
+            &["five"],
+            &["six"],
+            &["seven"],
+            &["eight"],
+            &["nine"],
+        ],
+    ),
+    (
+        "swedish",
+        &[
+            &["noll"],
+            &["ett", "en"],
+            &["två"],
+            &["tre"],
+            &["fyra"],
+            &["fem"],
+            &["sex"],
+            &["sju"],
+            &["åtta"],
+            &["nio"],
+        ],
+    ),
+    (
+        "german",
+        &[
+            &["null"],
+            &["eins"],
+            &["zwei"],
+            &["drei"],
+            &["vier"],
+            &["fünf"],
+            &["sechs"],
+            &["sieben"],
+            &["acht"],
+            &["neun"],
+        ],
+    ),
+    (
+        "french",
+        &[
+            &["zéro"],
+            &["un", "une"],
+            &["deux"],
+            &["trois"],
+            &["quatre"],
+            &["cinq"],
+            &["six"],
+            &["sept"],
+            &["huit"],
+            &["neuf"],
+        ],
+    ),
 ];
 
-/// Return an iterator of all possible prefixes of a string
+/// Words that spell out digits in part 2, in addition to the numerals
 ///
-/// i.e. "abc" will field ["a", "ab", "abc"]
-fn prefixes(s: &str) -> impl Iterator<Item = &str> {
-    (1..=s.len()).map(|n| &s[..n])
+/// Set the `AOC_DAY01_VOCABULARY` option to the name of a built-in language, or
+/// `AOC_DAY01_VOCABULARY_FILE` to the path of a file with one `word = digit` per line. Empty lines
+/// and lines starting with `#` are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vocabulary {
+    words: Vec<(String, u64)>,
 }
 
-/// Return an iterator of all possible suffixes of a string
-///
-/// i.e. "abc" will field ["c", "bc", "abc"]
-fn suffixes(s: &str) -> impl Iterator<Item = &str> {
-    (1..=s.len()).map(|n| &s[s.len() - n..])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VocabularyError {
+    /// The vocabulary file couldn't be read.
+    Io { path: String, error: String },
+
+    /// There's no built-in vocabulary for the language.
+    UnknownLanguage { name: String },
+
+    /// Both a language and a file were picked.
+    Conflict,
+
+    /// A line in a vocabulary file isn't `word = digit`.
+    Syntax { line: usize },
+
+    /// A word maps to something other than a single digit.
+    NotADigit { line: usize, value: String },
+}
+
+impl Display for VocabularyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VocabularyError::Io { path, error } => {
+                write!(f, "failed to read vocabulary file {path:?}: {error}")
+            }
+            VocabularyError::UnknownLanguage { name } => {
+                let languages: Vec<_> = LANGUAGES.iter().map(|(name, _)| *name).collect();
+                write!(
+                    f,
+                    "unknown language {name:?}, expected one of: {}",
+                    languages.join(", ")
+                )
+            }
+            VocabularyError::Conflict => write!(
+                f,
+                "{} and {} are both set, pick one",
+                options::var_name(module_path!(), "vocabulary"),
+                options::var_name(module_path!(), "vocabulary_file")
+            ),
+            VocabularyError::Syntax { line } => {
+                write!(f, "line {line}: expected a line like \"word = digit\"")
+            }
+            VocabularyError::NotADigit { line, value } => {
+                write!(f, "line {line}: {value:?} is not a digit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VocabularyError {}
+
+impl Vocabulary {
+    /// Get a built-in vocabulary by name, e.g. "english".
+    pub fn builtin(language: &str) -> Option<Self> {
+        let (_, digits) = LANGUAGES.iter().find(|(name, _)| *name == language)?;
+        let words = (0..)
+            .zip(digits.iter())
+            .flat_map(|(d, words)| words.iter().map(move |w| (w.to_string(), d)))
+            .collect();
+        Some(Vocabulary { words })
+    }
+
+    /// Parse a vocabulary from lines of `word = digit`.
+    pub fn parse(text: &str) -> Result<Self, VocabularyError> {
+        let mut words = vec![];
+        for (line, text) in (1..).zip(text.lines()) {
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            let (word, value) = text
+                .split_once('=')
+                .map(|(word, value)| (word.trim(), value.trim()))
+                .filter(|(word, _)| !word.is_empty())
+                .ok_or(VocabularyError::Syntax { line })?;
+
+            let digit = value.parse().ok().filter(|&d| d < 10).ok_or_else(|| {
+                VocabularyError::NotADigit {
+                    line,
+                    value: value.to_string(),
+                }
+            })?;
+
+            words.push((word.to_string(), digit));
+        }
+        Ok(Vocabulary { words })
+    }
+
+    /// Get a built-in vocabulary for `language`, or read one from `file`. English if neither is
+    /// given.
+    pub fn load(language: Option<&str>, file: Option<&str>) -> Result<Self, VocabularyError> {
+        match (language, file) {
+            (None, None) => Ok(Vocabulary::default()),
+            (Some(language), None) => {
+                Vocabulary::builtin(language).ok_or_else(|| VocabularyError::UnknownLanguage {
+                    name: language.to_string(),
+                })
+            }
+            (None, Some(path)) => {
+                let text = fs::read_to_string(path).map_err(|e| VocabularyError::Io {
+                    path: path.to_string(),
+                    error: e.to_string(),
+                })?;
+                Vocabulary::parse(&text)
+            }
+            (Some(_), Some(_)) => Err(VocabularyError::Conflict),
+        }
+    }
+
+    /// Get the vocabulary picked with the `AOC_DAY01_VOCABULARY` or `AOC_DAY01_VOCABULARY_FILE`
+    /// option, or English by default.
+    ///
+    /// Panics if the options don't pick a valid vocabulary, see [Vocabulary::load].
+    pub fn from_options() -> Self {
+        let language = options::get(module_path!(), "vocabulary");
+        let file = options::get(module_path!(), "vocabulary_file");
+        Vocabulary::load(language.as_deref(), file.as_deref())
+            .unwrap_or_else(|e| panic!("invalid vocabulary: {e}"))
+    }
+
+    /// Build an automaton that finds numerals and all words in the vocabulary.
+    pub fn matcher(&self) -> AhoCorasick<u64> {
+        let numerals = (0..10).map(|d| (d.to_string(), d));
+        AhoCorasick::new(numerals.chain(self.words.iter().cloned()))
+    }
 }
 
-/// Get the first character of a str (as a str)
-fn first_char(s: &str) -> &str {
-    &s[..1]
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Vocabulary::builtin("english").expect("english is a built-in language")
+    }
 }
 
-/// Get the first character of a str (as a str)
-fn last_char(s: &str) -> &str {
-    &s[s.len() - 1..]
+lazy_static! {
+    static ref NUMERALS: AhoCorasick<u64> = AhoCorasick::new((0..10).map(|d| (d.to_string(), d)));
+    static ref NUMERALS_AND_WORDS: AhoCorasick<u64> = Vocabulary::from_options().matcher();
 }
 
-pub fn part1(input: &str) -> u64 {
-    parse(input)
-        .map(|line| {
-            let first: u64 = prefixes(line)
-                .find_map(|prefix| last_char(prefix).parse().ok())
-                .unwrap_or_else(|| panic!("Failed to find first digit in {line:?}"));
+/// A digit found in a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digit {
+    /// Byte range of the numeral or word in the line.
+    pub span: Range<usize>,
+    pub value: u64,
+}
+
+/// What happened to a line when calibrating. See [report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The line has a calibration value.
+    Value {
+        first: Digit,
+        last: Digit,
+        value: u64,
+    },
 
-            let last: u64 = suffixes(line)
-                .find_map(|suffix| first_char(suffix).parse().ok())
-                .unwrap_or_else(|| panic!("Failed to find last digit in {line:?}"));
+    /// The line is blank.
+    Skipped,
+
+    /// The line doesn't contain any digits.
+    Invalid,
+}
 
-            first * 10 + last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineReport<'a> {
+    /// 1-indexed line number.
+    pub number: usize,
+    pub text: &'a str,
+    pub outcome: Outcome,
+}
+
+/// Per-line diagnostics for the calibration document, see [report]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report<'a> {
+    pub lines: Vec<LineReport<'a>>,
+}
+
+impl Report<'_> {
+    /// Sum of the values of all valid lines.
+    pub fn total(&self) -> u64 {
+        self.lines
+            .iter()
+            .filter_map(|line| match line.outcome {
+                Outcome::Value { value, .. } => Some(value),
+                _ => None,
+            })
+            .sum_ck()
+    }
+
+    fn count(&self, f: impl Fn(&Outcome) -> bool) -> usize {
+        self.lines.iter().filter(|line| f(&line.outcome)).count()
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for LineReport {
+            number,
+            text,
+            outcome,
+        } in &self.lines
+        {
+            write!(f, "line {number}: ")?;
+            match outcome {
+                Outcome::Value { first, last, value } => {
+                    let word = |d: &Digit| &text[d.span.clone()];
+                    writeln!(
+                        f,
+                        "{value:02}, first {:?} at {:?}, last {:?} at {:?}",
+                        word(first),
+                        first.span,
+                        word(last),
+                        last.span
+                    )?;
+                }
+                Outcome::Skipped => writeln!(f, "skipped blank line")?,
+                Outcome::Invalid => writeln!(f, "invalid, no digits in {text:?}")?,
+            }
+        }
+
+        let valid = self.count(|o| matches!(o, Outcome::Value { .. }));
+        let skipped = self.count(|o| *o == Outcome::Skipped);
+        let invalid = self.count(|o| *o == Outcome::Invalid);
+        writeln!(
+            f,
+            "total {} from {valid} lines, {skipped} skipped, {invalid} invalid",
+            self.total()
+        )
+    }
+}
+
+/// Find the first and last digit in a line, and combine them into a two-digit number.
+fn check_line(matcher: &AhoCorasick<u64>, line: &str) -> Outcome {
+    if line.trim().is_empty() {
+        return Outcome::Skipped;
+    }
+
+    let Some((first, last)) = matcher.first_last(line.as_bytes()) else {
+        return Outcome::Invalid;
+    };
+
+    let digit = |m: Match<u64>| Digit {
+        span: m.start..m.end,
+        value: *m.value,
+    };
+    Outcome::Value {
+        value: first.value.mul_ck(10).add_ck(*last.value),
+        first: digit(first),
+        last: digit(last),
+    }
+}
+
+/// Check every line of the input, without panicking on lines that don't have a value.
+pub fn report<'a>(input: &'a str, matcher: &AhoCorasick<u64>) -> Report<'a> {
+    let lines = (1..)
+        .zip(parse(input))
+        .map(|(number, text)| LineReport {
+            number,
+            text,
+            outcome: check_line(matcher, text),
         })
-        .sum()
+        .collect();
+    Report { lines }
 }
 
-pub fn part2(input: &str) -> u64 {
+/// Sum the values of all lines. Panics if a line doesn't have a value.
+fn calibrate(input: &str, matcher: &AhoCorasick<u64>) -> u64 {
     parse(input)
-        .map(|line| {
-            let first: u64 = prefixes(line)
-                .find_map(|prefix| {
-                    last_char(prefix).parse().ok().or_else(|| {
-                        DIGIT_NAMES
-                            .iter()
-                            .enumerate()
-                            .find_map(|(i, name)| prefix.ends_with(name).then_some(i as u64))
-                    })
-                })
-                .unwrap_or_else(|| panic!("Failed to find first digit in {line:?}"));
-
-            let last: u64 = suffixes(line)
-                .find_map(|suffix| {
-                    first_char(suffix).parse().ok().or_else(|| {
-                        DIGIT_NAMES
-                            .iter()
-                            .enumerate()
-                            .find_map(|(i, name)| suffix.starts_with(name).then_some(i as u64))
-                    })
-                })
-                .unwrap_or_else(|| panic!("Failed to find last digit in {line:?}"));
-
-            first * 10 + last
+        .map(|line| match check_line(matcher, line) {
+            Outcome::Value { value, .. } => value,
+            _ => panic!("Failed to find first digit in {line:?}"),
         })
-        .sum()
+        .sum_ck()
+}
+
+/// Solve a part by finding digits with `matcher`.
+///
+/// In report mode, print what happened to each line to stderr, and skip lines without a value
+/// instead of panicking.
+fn solve(input: &str, matcher: &AhoCorasick<u64>, report_mode: bool) -> u64 {
+    if !report_mode {
+        return calibrate(input, matcher);
+    }
+
+    let report = report(input, matcher);
+    eprint!("{report}");
+    report.total()
+}
+
+pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
+    let report_mode = options::get(module_path!(), "report").is_some();
+    solve(input, &NUMERALS, report_mode)
+}
+
+pub fn part2(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 2);
+    let report_mode = options::get(module_path!(), "report").is_some();
+    solve(input, &NUMERALS_AND_WORDS, report_mode)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test::{black_box, Bencher};
 
     #[test]
     pub fn test_part1() {
@@ -87,9 +400,248 @@ mod tests {
         assert_eq!(part1(input), 142);
     }
 
+    /// Part 2 with the default vocabulary, whatever the `AOC_DAY01_VOCABULARY` option is set to
+    fn part2_english(input: &str) -> u64 {
+        calibrate(input, &Vocabulary::default().matcher())
+    }
+
     #[test]
     pub fn test_part2() {
         let input = include_str!("test-input2");
-        assert_eq!(part2(input), 281);
+        assert_eq!(part2_english(input), 281);
+    }
+
+    #[test]
+    pub fn test_part2_options() {
+        // every line has a numeral, so this has an answer whatever vocabulary the options pick,
+        // and part 2 should agree with calibrating with that vocabulary
+        let input = "two1nine\nabcone2threexyz\n";
+        let vocabulary = Vocabulary::from_options();
+        assert_eq!(part2(input), calibrate(input, &vocabulary.matcher()));
+    }
+
+    fn prefixes(s: &str) -> impl Iterator<Item = &str> {
+        (1..=s.len()).map(|n| &s[..n])
+    }
+
+    fn suffixes(s: &str) -> impl Iterator<Item = &str> {
+        (1..=s.len()).map(|n| &s[s.len() - n..])
+    }
+
+    /// The old implementation of part 2, which checks every prefix and suffix of each line
+    /// against every digit name
+    fn part2_prefixes(input: &str) -> u64 {
+        let words = Vocabulary::default().words;
+        parse(input)
+            .map(|line| {
+                let first: u64 = prefixes(line)
+                    .find_map(|prefix| {
+                        prefix[prefix.len() - 1..].parse().ok().or_else(|| {
+                            words
+                                .iter()
+                                .find_map(|(name, i)| prefix.ends_with(name.as_str()).then_some(*i))
+                        })
+                    })
+                    .unwrap();
+
+                let last: u64 = suffixes(line)
+                    .find_map(|suffix| {
+                        suffix[..1].parse().ok().or_else(|| {
+                            words.iter().find_map(|(name, i)| {
+                                suffix.starts_with(name.as_str()).then_some(*i)
+                            })
+                        })
+                    })
+                    .unwrap();
+
+                first * 10 + last
+            })
+            .sum()
+    }
+
+    #[test]
+    pub fn test_report() {
+        let input = "two1nine\n\nabc\neightwothree\nxπ7\n";
+        let report = report(input, &Vocabulary::default().matcher());
+        let digit = |span, value| Digit { span, value };
+
+        assert_eq!(
+            report.lines[0].outcome,
+            Outcome::Value {
+                first: digit(0..3, 2),
+                last: digit(4..8, 9),
+                value: 29
+            }
+        );
+        assert_eq!(report.lines[1].outcome, Outcome::Skipped);
+        assert_eq!(report.lines[2].outcome, Outcome::Invalid);
+        assert_eq!(
+            report.lines[3].outcome,
+            Outcome::Value {
+                first: digit(0..5, 8),
+                last: digit(7..12, 3),
+                value: 83
+            }
+        );
+        assert_eq!(
+            report.lines[4].outcome,
+            Outcome::Value {
+                first: digit(3..4, 7),
+                last: digit(3..4, 7),
+                value: 77
+            }
+        );
+        assert_eq!(report.total(), 29 + 83 + 77);
+
+        let text = report.to_string();
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next(),
+            Some("line 1: 29, first \"two\" at 0..3, last \"nine\" at 4..8")
+        );
+        assert_eq!(lines.next(), Some("line 2: skipped blank line"));
+        assert_eq!(lines.next(), Some("line 3: invalid, no digits in \"abc\""));
+        assert_eq!(
+            lines.last(),
+            Some("total 189 from 3 lines, 1 skipped, 1 invalid")
+        );
+    }
+
+    #[test]
+    pub fn test_report_mode() {
+        // report mode uses the same vocabulary as the normal mode
+        let swedish = Vocabulary::builtin("swedish").unwrap().matcher();
+        let input = "tvåttanio\nsjuttiotre\n";
+        assert_eq!(solve(input, &swedish, true), 29 + 73);
+        assert_eq!(solve(input, &swedish, false), 29 + 73);
+
+        // and skips lines without digits
+        assert_eq!(solve("tvåttanio\ntwo\n", &swedish, true), 29);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to find first digit in \"abc\"")]
+    pub fn test_invalid_line() {
+        calibrate("1abc2\nabc\n", &NUMERALS);
+    }
+
+    #[test]
+    pub fn test_vocabularies() {
+        let english = Vocabulary::default();
+        assert_eq!(english.words.len(), 10);
+        assert_eq!(english.words[3], ("three".to_string(), 3));
+        assert_eq!(Vocabulary::builtin("klingon"), None);
+
+        let swedish = Vocabulary::builtin("swedish").unwrap().matcher();
+        assert_eq!(
+            calibrate("tvåttanio\nsjuttiotre\nenett\n", &swedish),
+            29 + 73 + 11
+        );
+
+        let french = Vocabulary::builtin("french").unwrap().matcher();
+        assert_eq!(calibrate("zérouneuf\nquatrequinze\n", &french), 9 + 44);
+
+        // words can share prefixes and overlap
+        let vocabulary = Vocabulary::parse("# test\nab = 1\n\nabc=2\n  bcd = 3\nπ = 4\n").unwrap();
+        let matcher = vocabulary.matcher();
+        assert_eq!(calibrate("xabcdx\nπ\n", &matcher), 13 + 44);
+    }
+
+    #[test]
+    pub fn test_vocabulary_errors() {
+        assert_eq!(
+            Vocabulary::parse("one = 1\ntwo\n"),
+            Err(VocabularyError::Syntax { line: 2 })
+        );
+        assert_eq!(
+            Vocabulary::parse("ten = 10"),
+            Err(VocabularyError::NotADigit {
+                line: 1,
+                value: "10".to_string()
+            })
+        );
+        assert_eq!(
+            Vocabulary::parse(" = 1").unwrap_err().to_string(),
+            "line 1: expected a line like \"word = digit\""
+        );
+    }
+
+    #[test]
+    pub fn test_load_vocabulary() {
+        assert_eq!(Vocabulary::load(None, None), Ok(Vocabulary::default()));
+        assert_eq!(
+            Vocabulary::load(Some("german"), None),
+            Ok(Vocabulary::builtin("german").unwrap())
+        );
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/day01/test-vocabulary");
+        let vocabulary = Vocabulary::load(None, Some(path)).unwrap();
+        assert_eq!(
+            calibrate("xunox\ntresdos\n", &vocabulary.matcher()),
+            11 + 32
+        );
+
+        // a typo in a language name isn't mistaken for a missing file
+        let typo = Vocabulary::load(Some("swedsh"), None).unwrap_err();
+        assert_eq!(
+            typo.to_string(),
+            "unknown language \"swedsh\", expected one of: english, swedish, german, french"
+        );
+
+        let missing = Vocabulary::load(None, Some("no/such/vocabulary")).unwrap_err();
+        assert!(
+            matches!(missing, VocabularyError::Io { ref path, .. } if path == "no/such/vocabulary")
+        );
+        assert!(missing
+            .to_string()
+            .starts_with("failed to read vocabulary file"));
+
+        assert_eq!(
+            Vocabulary::load(Some("swedish"), Some(path))
+                .unwrap_err()
+                .to_string(),
+            "AOC_DAY01_VOCABULARY and AOC_DAY01_VOCABULARY_FILE are both set, pick one"
+        );
+    }
+
+    #[test]
+    pub fn test_overlapping_names() {
+        let input = "twone\neightwothree\noneight\n7pqrstsixteen\n";
+        assert_eq!(part2_english(input), part2_prefixes(input));
+        assert_eq!(part2_english(input), 21 + 83 + 18 + 76);
+    }
+
+    /// Long lines where the digits are far from both ends, which is the worst case for
+    /// [part2_prefixes].
+    fn long_lines() -> String {
+        let padding = "abcdefghijklmnopqrstuvwxyz".repeat(4);
+        let line = format!("{padding}two1nine{padding}\n");
+        line.repeat(100)
+    }
+
+    #[bench]
+    fn bench_part2_automaton(b: &mut Bencher) {
+        let input = include_str!("test-input2");
+        let matcher = Vocabulary::default().matcher();
+        b.iter(|| calibrate(black_box(input), &matcher));
+    }
+
+    #[bench]
+    fn bench_part2_prefixes(b: &mut Bencher) {
+        let input = include_str!("test-input2");
+        b.iter(|| part2_prefixes(black_box(input)));
+    }
+
+    #[bench]
+    fn bench_part2_automaton_long_lines(b: &mut Bencher) {
+        let input = long_lines();
+        let matcher = Vocabulary::default().matcher();
+        b.iter(|| calibrate(black_box(&input), &matcher));
+    }
+
+    #[bench]
+    fn bench_part2_prefixes_long_lines(b: &mut Bencher) {
+        let input = long_lines();
+        b.iter(|| part2_prefixes(black_box(&input)));
     }
 }
@@ -1,103 +1,532 @@
-use crate::util::HashMap;
+use crate::util::{
+    checked::{self, CheckedIter},
+    intern::Interner,
+    options,
+    parse::{ParseResult, Parser},
+    parse_u64, simd,
+};
 use std::cmp::max;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+use std::fs;
+
+/// A color, as an ID from [Games::colors]
+pub type Color = u32;
+
+type ColorCount = (Color, u64);
 
 pub struct Game {
     id: u64,
     revealed: Vec<Vec<ColorCount>>,
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
-#[repr(u8)]
-pub enum Color {
-    Red,
-    Green,
-    Blue,
+pub struct Games<'a> {
+    /// Every color that appears in any game, in the order they first appear
+    colors: Interner<'a>,
+    games: Vec<Game>,
 }
 
-type ColorCount = (Color, u64);
+/// Parse `<count> <color>`, e.g. "3 blue".
+fn cubes<'a>(p: &mut Parser<'a>) -> ParseResult<(u64, &'a str)> {
+    let count = p.int()?;
+    p.lit(" ")?;
+    let color = p.alnum()?;
+    Ok((count, color))
+}
+
+pub fn parse(input: &str) -> Games<'_> {
+    let mut colors = Interner::new();
+    let games = Parser::new(input)
+        .all(|p| {
+            p.lines(|p| {
+                p.lit("Game ")?;
+                let id = p.int()?;
+                p.lit(": ")?;
+                let revealed = p.sep_by("; ", |p| {
+                    p.sep_by(", ", |p| {
+                        let (count, color) = cubes(p)?;
+                        Ok((colors.intern(color), count))
+                    })
+                })?;
+
+                Ok(Game { id, revealed })
+            })
+        })
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    Games { colors, games }
+}
+
+/// How many cubes of each color the bag holds in part 1
+///
+/// Set the `AOC_DAY02_BAG` option to the contents, written like a reveal (e.g.
+/// `12 red, 13 green, 14 blue`), or `AOC_DAY02_BAG_FILE` to the path of a file with the contents.
+/// Colors that aren't in the bag have a limit of 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag {
+    limits: Vec<(String, u64)>,
+}
+
+impl Bag {
+    /// Parse the contents of a bag, e.g. "12 red, 13 green, 14 blue".
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        let limits = Parser::new(text.trim()).all(|p| p.sep_by(", ", cubes))?;
+        let limits = limits
+            .into_iter()
+            .map(|(count, color)| (color.to_string(), count))
+            .collect();
+        Ok(Bag { limits })
+    }
 
-pub fn parse(input: &str) -> Vec<Game> {
-    input
-        .lines()
-        .map(|line| {
-            let (id, line) = line.split_once(": ").unwrap();
-            let id = id.trim_start_matches("Game ").parse().unwrap();
-            let revealed = line
-                .split("; ")
-                .map(|game| {
-                    game.split(", ")
-                        .map(|c| {
-                            let (count, color) = c.split_once(' ').unwrap();
-                            let color = match color.as_bytes()[0] {
-                                b'r' => Color::Red,
-                                b'g' => Color::Green,
-                                b'b' => Color::Blue,
-                                _ => panic!(),
-                            };
-                            (color, count.parse().unwrap())
-                        })
-                        .collect()
+    /// Get the bag set with the `AOC_DAY02_BAG` or `AOC_DAY02_BAG_FILE` option, or the one from
+    /// the puzzle by default.
+    ///
+    /// Panics if both options are set, if the file can't be read, or if the contents aren't a
+    /// valid bag.
+    pub fn from_options() -> Self {
+        let text = match (
+            options::get(module_path!(), "bag"),
+            options::get(module_path!(), "bag_file"),
+        ) {
+            (None, None) => return Bag::default(),
+            (Some(text), None) => text,
+            (None, Some(path)) => fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("failed to read bag file {path:?}: {e}")),
+            (Some(_), Some(_)) => panic!(
+                "{} and {} are both set, pick one",
+                options::var_name(module_path!(), "bag"),
+                options::var_name(module_path!(), "bag_file")
+            ),
+        };
+        Bag::parse(&text).unwrap_or_else(|e| panic!("invalid bag: {e}"))
+    }
+
+    /// The number of cubes of `color` in the bag.
+    pub fn limit(&self, color: &str) -> u64 {
+        self.limits
+            .iter()
+            .filter(|(c, _)| c == color)
+            .map(|&(_, count)| count)
+            .sum_ck()
+    }
+
+    /// The limit of each color, indexed by ID.
+    fn limits(&self, colors: &Interner) -> Vec<u64> {
+        colors.iter().map(|(_, name, _)| self.limit(name)).collect()
+    }
+}
+
+impl Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes: Vec<_> = self
+            .limits
+            .iter()
+            .map(|(color, count)| format!("{count} {color}"))
+            .collect();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl Default for Bag {
+    fn default() -> Self {
+        let limits = [("red", 12), ("green", 13), ("blue", 14)];
+        Bag {
+            limits: limits.map(|(c, n)| (c.to_string(), n)).to_vec(),
+        }
+    }
+}
+
+/// A reveal that showed more cubes of a color than the bag holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exceeded<'a> {
+    /// 1-indexed number of the reveal within the game.
+    pub reveal: usize,
+    pub color: &'a str,
+    pub count: u64,
+    pub limit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameReport<'a> {
+    pub id: u64,
+
+    /// Every limit that was exceeded. The game is possible if this is empty.
+    pub exceeded: Vec<Exceeded<'a>>,
+}
+
+/// Which games are possible with a bag, and why the others aren't. See [report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report<'a> {
+    pub games: Vec<GameReport<'a>>,
+}
+
+impl Report<'_> {
+    /// Sum of the IDs of the possible games.
+    pub fn total(&self) -> u64 {
+        self.games
+            .iter()
+            .filter(|game| game.exceeded.is_empty())
+            .map(|game| game.id)
+            .sum_ck()
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for GameReport { id, exceeded } in &self.games {
+            write!(f, "game {id}: ")?;
+            if exceeded.is_empty() {
+                writeln!(f, "possible")?;
+                continue;
+            }
+
+            let reasons: Vec<_> = exceeded
+                .iter()
+                .map(|e| {
+                    format!(
+                        "reveal {} has {} {}, limit {}",
+                        e.reveal, e.count, e.color, e.limit
+                    )
                 })
                 .collect();
+            writeln!(f, "impossible, {}", reasons.join("; "))?;
+        }
 
-            Game { id, revealed }
-        })
-        .collect()
+        let possible = self.games.iter().filter(|g| g.exceeded.is_empty()).count();
+        writeln!(
+            f,
+            "total {} from {possible} possible games, {} impossible",
+            self.total(),
+            self.games.len() - possible
+        )
+    }
 }
 
-pub fn part1(input: &str) -> u64 {
-    let games = parse(input);
-    let bag: HashMap<_, u64> = [(Color::Red, 12), (Color::Green, 13), (Color::Blue, 14)]
-        .into_iter()
+/// Check every reveal of every game against the limits of `bag`.
+pub fn report<'a>(games: &Games<'a>, bag: &Bag) -> Report<'a> {
+    let limits = bag.limits(&games.colors);
+    let games = games
+        .games
+        .iter()
+        .map(|game| {
+            let exceeded = (1..)
+                .zip(&game.revealed)
+                .flat_map(|(reveal, r)| r.iter().map(move |&cubes| (reveal, cubes)))
+                .filter(|&(_, (color, count))| count > limits[color as usize])
+                .map(|(reveal, (color, count))| Exceeded {
+                    reveal,
+                    color: games.colors.name(color),
+                    count,
+                    limit: limits[color as usize],
+                })
+                .collect();
+
+            GameReport {
+                id: game.id,
+                exceeded,
+            }
+        })
         .collect();
+    Report { games }
+}
 
-    let mut revealed: HashMap<Color, u64> = Default::default();
+impl<'a> Games<'a> {
+    /// The fewest cubes of each color that each game needs, indexed by game and then by color.
+    fn needs(&self) -> Vec<Vec<u64>> {
+        self.games
+            .iter()
+            .map(|game| {
+                let mut need = vec![0; self.colors.len()];
+                for &(color, count) in game.revealed.iter().flatten() {
+                    need[color as usize] = max(need[color as usize], count);
+                }
+                need
+            })
+            .collect()
+    }
 
-    games
-        .into_iter()
-        .filter_map(|game| {
-            for r in &game.revealed {
-                revealed.clear();
-                revealed.extend(r.iter().copied());
+    /// Make a bag with `counts[color]` cubes of each color.
+    fn bag(&self, counts: &[u64]) -> Bag {
+        let limits = self.colors.iter().zip(counts);
+        Bag {
+            limits: limits
+                .map(|((_, name, _), &count)| (name.to_string(), count))
+                .collect(),
+        }
+    }
 
-                for (&color, &count) in &revealed {
-                    let max = bag.get(&color).unwrap_or(&0);
-                    if count > *max {
-                        return None;
-                    }
+    /// The smallest bag that makes every game possible.
+    pub fn minimal_bag(&self) -> Bag {
+        let mut counts = vec![0; self.colors.len()];
+        for need in self.needs() {
+            for (count, n) in counts.iter_mut().zip(need) {
+                *count = max(*count, n);
+            }
+        }
+        self.bag(&counts)
+    }
+
+    /// Find the bags of at most `budget` cubes in total that make as many games as possible
+    /// possible.
+    ///
+    /// Only the smallest such bags are returned, i.e. removing any cube from them makes some
+    /// game impossible. Any bag that contains one of them and stays within the budget is just as
+    /// good.
+    ///
+    /// The count of each color is picked from what some game needs, skipping combinations as soon
+    /// as they go over the budget, and every bag that's left is checked against every game. With
+    /// a budget large enough for most games, that's close to `(games + 1) ^ colors` bags, so this
+    /// is only practical for a handful of colors.
+    pub fn best_bags(&self, budget: u64) -> BestBags {
+        let needs = self.needs();
+
+        // the count of a color in a smallest bag is always 0 or what some game needs
+        let candidates: Vec<Vec<u64>> = (0..self.colors.len())
+            .map(|color| {
+                let mut counts: Vec<_> = needs.iter().map(|need| need[color]).collect();
+                counts.push(0);
+                counts.retain(|&count| count <= budget);
+                counts.sort_unstable();
+                counts.dedup();
+                counts
+            })
+            .collect();
+
+        let mut possible_max = 0;
+        let mut best = BTreeSet::new();
+        within_budget(&candidates, budget, &mut vec![], &mut |counts| {
+            // shrink the bag to exactly what the possible games need, so that different
+            // candidates that allow the same games end up as the same bag
+            let mut tight = vec![0; counts.len()];
+            let mut possible = 0;
+            for need in needs
+                .iter()
+                .filter(|need| need.iter().zip(counts).all(|(n, c)| n <= c))
+            {
+                possible += 1;
+                for (t, &n) in tight.iter_mut().zip(need) {
+                    *t = max(*t, n);
                 }
             }
 
-            Some(game.id)
+            if possible > possible_max {
+                possible_max = possible;
+                best.clear();
+            }
+            if possible == possible_max {
+                best.insert(tight);
+            }
+        });
+
+        BestBags {
+            possible: possible_max,
+            bags: best.iter().map(|counts| self.bag(counts)).collect(),
+        }
+    }
+
+    /// For each color, how many games need each number of cubes of it. Games that never show a
+    /// color need 0 of it.
+    pub fn distribution(&self) -> Vec<(&'a str, BTreeMap<u64, usize>)> {
+        let needs = self.needs();
+        self.colors
+            .iter()
+            .map(|(color, name, _)| {
+                let mut counts = BTreeMap::new();
+                for need in &needs {
+                    *counts.entry(need[color as usize]).or_default() += 1;
+                }
+                (name, counts)
+            })
+            .collect()
+    }
+}
+
+/// Call `visit` with every way to pick one count from each list in `candidates`, where the counts
+/// add up to at most `budget`. Each list must be sorted.
+fn within_budget(
+    candidates: &[Vec<u64>],
+    budget: u64,
+    counts: &mut Vec<u64>,
+    visit: &mut impl FnMut(&[u64]),
+) {
+    let Some((first, rest)) = candidates.split_first() else {
+        visit(counts);
+        return;
+    };
+
+    for &count in first.iter().take_while(|&&count| count <= budget) {
+        counts.push(count);
+        within_budget(rest, budget - count, counts, visit);
+        counts.pop();
+    }
+}
+
+/// Result of [Games::best_bags]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestBags {
+    /// Number of games that are possible with each of the bags.
+    pub possible: usize,
+    pub bags: Vec<Bag>,
+}
+
+/// A game that's only been parsed up to its ID, see [stream]
+#[derive(Debug, Clone, Copy)]
+pub struct LazyGame<'a> {
+    pub id: u64,
+
+    /// The reveals, e.g. "3 blue, 4 red; 1 red".
+    reveals: &'a str,
+}
+
+/// A reveal that hasn't been parsed yet, e.g. "3 blue, 4 red"
+#[derive(Debug, Clone, Copy)]
+pub struct LazyReveal<'a> {
+    game: u64,
+    text: &'a str,
+}
+
+impl<'a> LazyGame<'a> {
+    pub fn reveals(&self) -> impl Iterator<Item = LazyReveal<'a>> + 'a {
+        let game = self.id;
+        self.reveals
+            .split("; ")
+            .map(move |text| LazyReveal { game, text })
+    }
+
+    /// All cubes shown in any reveal of the game.
+    pub fn cubes(&self) -> impl Iterator<Item = (u64, &'a str)> + 'a {
+        self.reveals().flat_map(|reveal| reveal.cubes())
+    }
+}
+
+/// Parse a count or ID the same way [Parser::int] does for the full parser, i.e. only digits.
+fn number(s: &str) -> Option<u64> {
+    Some(s).filter(|s| !s.is_empty()).and_then(parse_u64)
+}
+
+impl<'a> LazyReveal<'a> {
+    /// The count and color of each kind of cube shown.
+    ///
+    /// Panics if the cubes aren't written as `<count> <color>`.
+    pub fn cubes(&self) -> impl Iterator<Item = (u64, &'a str)> + 'a {
+        let game = self.game;
+        self.text.split(", ").map(move |text| {
+            text.split_once(' ')
+                .and_then(|(count, color)| Some((number(count)?, color)))
+                .filter(|(_, color)| !color.is_empty() && color.chars().all(char::is_alphanumeric))
+                .unwrap_or_else(|| {
+                    panic!("game {game}: expected \"<count> <color>\", found {text:?}")
+                })
         })
-        .sum()
+    }
+}
+
+/// Parse the games lazily, without allocating.
+///
+/// Each line is only split into its ID and reveals up front. The reveals are parsed while
+/// iterating over them, so a malformed reveal only panics once it's reached.
+pub fn stream(input: &str) -> impl Iterator<Item = LazyGame<'_>> {
+    simd::lines(input).map(|line| {
+        line.strip_prefix("Game ")
+            .and_then(|line| line.split_once(": "))
+            .and_then(|(id, reveals)| {
+                Some(LazyGame {
+                    id: number(id)?,
+                    reveals,
+                })
+            })
+            .unwrap_or_else(|| panic!("expected \"Game <id>: <reveals>\", found {line:?}"))
+    })
+}
+
+/// The product of the fewest cubes of each color the game needs.
+///
+/// `fewest` is scratch space for the largest count of each color. It's reused between games, so
+/// that it only allocates when a game shows more colors than any game before it.
+fn power<'a>(game: &LazyGame<'a>, fewest: &mut Vec<(&'a str, u64)>) -> u64 {
+    fewest.clear();
+    for (count, color) in game.cubes() {
+        match fewest.iter_mut().find(|(c, _)| *c == color) {
+            Some((_, n)) => *n = max(*n, count),
+            None => fewest.push((color, count)),
+        }
+    }
+    fewest.iter().map(|&(_, n)| n).product_ck()
+}
+
+pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
+    let bag = Bag::from_options();
+
+    // the report needs all games in memory, so it uses the full parser
+    if options::get(module_path!(), "report").is_some() {
+        let report = report(&parse(input), &bag);
+        eprint!("{report}");
+        return report.total();
+    }
+
+    possible_ids(input, &bag)
+}
+
+/// Sum the IDs of the games that are possible with `bag`, on top of [stream].
+fn possible_ids(input: &str, bag: &Bag) -> u64 {
+    stream(input)
+        .filter(|game| game.cubes().all(|(count, color)| count <= bag.limit(color)))
+        .map(|game| game.id)
+        .sum_ck()
 }
 
 pub fn part2(input: &str) -> u64 {
-    let games = parse(input);
+    let _ctx = checked::context(module_path!(), 2);
+    let mut fewest = vec![];
+    stream(input).map(|game| power(&game, &mut fewest)).sum_ck()
+}
 
-    let mut revealed: HashMap<Color, u64> = Default::default();
+// Entry points for questions about the games other than part 1 and 2. The command line is
+// generated by `aoc_macro::generate_days!`, which only knows about `part1` and `part2`, so these
+// can't be subcommands and nothing in the binary calls them.
 
-    games
-        .into_iter()
-        .map(|game| {
-            revealed.clear();
-            for r in &game.revealed {
-                for &(color, count) in r {
-                    let e = revealed.entry(color).or_default();
-                    *e = max(*e, count);
-                }
-            }
+/// The smallest bag that makes every game possible, see [Games::minimal_bag].
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn minimal_bag(input: &str) -> String {
+    format!("minimal bag: {}\n", parse(input).minimal_bag())
+}
 
-            let power: u64 = revealed.iter().map(|(_, &count)| count).product();
-            power
-        })
-        .sum()
+/// The smallest bags of at most `budget` cubes that allow the most games, see
+/// [Games::best_bags]. This gets slow with more than a few colors.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn best_bags(input: &str, budget: u64) -> String {
+    let games = parse(input);
+    let best = games.best_bags(budget);
+    let mut answer = format!(
+        "{} of {} games possible with at most {budget} cubes, with any of:\n",
+        best.possible,
+        games.games.len()
+    );
+    for bag in &best.bags {
+        answer += &format!("  {bag}\n");
+    }
+    answer
+}
+
+/// For each color, how many games need each number of cubes of it, see [Games::distribution].
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn distribution(input: &str) -> String {
+    let mut answer = String::new();
+    for (color, counts) in parse(input).distribution() {
+        let counts: Vec<_> = counts
+            .iter()
+            .map(|(need, games)| format!("{need}: {games}"))
+            .collect();
+        answer += &format!("{color}: {}\n", counts.join(", "));
+    }
+    answer
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::*;
+    use test::{black_box, Bencher};
 
     #[test]
     pub fn test_part1() {
@@ -110,4 +539,232 @@ mod tests {
         let input = include_str!("test-input");
         assert_eq!(part2(input), 2286);
     }
+
+    #[test]
+    pub fn test_default_bag() {
+        let input = include_str!("test-input");
+        let bag = Bag::default();
+        assert_eq!(bag.to_string(), "12 red, 13 green, 14 blue");
+        assert_eq!(Bag::parse("12 red, 13 green, 14 blue\n"), Ok(bag.clone()));
+        assert_eq!(report(&parse(input), &bag).total(), 8);
+    }
+
+    #[test]
+    pub fn test_colors() {
+        let games = parse("Game 7: 2 teal, 1 red; 3 teal\nGame 8: 1 mauve\n");
+        let names: Vec<_> = games.colors.iter().map(|(_, name, _)| name).collect();
+        assert_eq!(names, ["teal", "red", "mauve"]);
+
+        let bag = Bag::parse("2 teal, 1 red\n").unwrap();
+        assert_eq!(bag.limit("teal"), 2);
+        assert_eq!(bag.limit("mauve"), 0);
+        assert_eq!(report(&games, &bag).total(), 0);
+
+        let bag = Bag::parse("3 teal, 1 mauve").unwrap();
+        assert_eq!(report(&games, &bag).total(), 8);
+        assert!(Bag::parse("12 red,").is_err());
+    }
+
+    #[test]
+    pub fn test_queries() {
+        let input = include_str!("test-input");
+        let games = parse(input);
+        let minimal = games.minimal_bag();
+        assert_eq!(minimal.to_string(), "15 blue, 20 red, 13 green");
+        assert_eq!(Bag::parse(&minimal.to_string()), Ok(minimal));
+
+        // the puzzle's bag has 39 cubes, but 15 are enough for the same 3 games
+        let best = games.best_bags(15);
+        assert_eq!(best.possible, 3);
+        let bags: Vec<_> = best.bags.iter().map(Bag::to_string).collect();
+        assert_eq!(bags, ["6 blue, 6 red, 3 green"]);
+
+        // with one cube less, there's more than one way to pick 2 games
+        let best = games.best_bags(14);
+        assert_eq!(best.possible, 2);
+        let bags: Vec<_> = best.bags.iter().map(Bag::to_string).collect();
+        assert_eq!(bags, ["4 blue, 6 red, 3 green", "6 blue, 4 red, 3 green"]);
+
+        // every game needs some cubes, so an empty bag allows none of them
+        assert_eq!(games.best_bags(0).possible, 0);
+        let best = games.best_bags(1000);
+        assert_eq!(best.possible, 5);
+        assert_eq!(best.bags, [games.minimal_bag()]);
+
+        let distribution = games.distribution();
+        assert_eq!(distribution[0].0, "blue");
+        assert_eq!(
+            distribution[0].1.iter().collect::<Vec<_>>(),
+            [(&2, &1), (&4, &1), (&6, &2), (&15, &1)]
+        );
+
+        assert_eq!(
+            minimal_bag(input),
+            "minimal bag: 15 blue, 20 red, 13 green\n"
+        );
+        assert_eq!(
+            best_bags(input, 15),
+            "3 of 5 games possible with at most 15 cubes, with any of:\n  6 blue, 6 red, 3 green\n"
+        );
+        assert_eq!(
+            super::distribution(input).lines().next(),
+            Some("blue: 2: 1, 4: 1, 6: 2, 15: 1")
+        );
+    }
+
+    #[test]
+    pub fn test_report() {
+        let input = include_str!("test-input");
+        let games = parse(input);
+        let report = report(&games, &Bag::default());
+
+        assert_eq!(
+            report.games[3].exceeded,
+            [
+                Exceeded {
+                    reveal: 3,
+                    color: "blue",
+                    count: 15,
+                    limit: 14
+                },
+                Exceeded {
+                    reveal: 3,
+                    color: "red",
+                    count: 14,
+                    limit: 12
+                }
+            ]
+        );
+
+        let text = report.to_string();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "game 1: possible");
+        assert_eq!(
+            lines[2],
+            "game 3: impossible, reveal 1 has 20 red, limit 12"
+        );
+        assert_eq!(lines[5], "total 8 from 3 possible games, 2 impossible");
+    }
+
+    #[test]
+    pub fn test_stream() {
+        let input = include_str!("test-input");
+        let game = stream(input).nth(2).unwrap();
+        assert_eq!(game.id, 3);
+        assert_eq!(game.reveals().count(), 3);
+        let first: Vec<_> = game.reveals().next().unwrap().cubes().collect();
+        assert_eq!(first, [(8, "green"), (6, "blue"), (20, "red")]);
+        assert_eq!(power(&game, &mut vec![]), 20 * 13 * 6);
+
+        // same answers as the full parser, also with colors that aren't in the puzzle
+        let odd = "Game 7: 2 teal, 1 red; 3 teal\nGame 8: 1 mauve\n";
+        let bags = [
+            Bag::default(),
+            Bag::parse("3 teal, 1 mauve, 1 red").unwrap(),
+        ];
+        for input in [input, odd, &bench_input()] {
+            assert_eq!(part2_parse(input), part2(input));
+            for bag in &bags {
+                let total = report(&parse(input), bag).total();
+                assert_eq!(possible_ids(input, bag), total);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_many_colors() {
+        // more colors in a single game than would fit in a small fixed table
+        let cubes: Vec<_> = (1..=20).map(|n| format!("{n} color{n}")).collect();
+        let input = format!("Game 1: {}\nGame 2: 2 red; 3 red\n", cubes.join(", "));
+        let factorial: u64 = (1..=20).product();
+        assert_eq!(part2(&input), factorial + 3);
+        assert_eq!(part2_parse(&input), part2(&input));
+    }
+
+    #[test]
+    #[should_panic(expected = "game 2: expected \"<count> <color>\", found \"3green\"")]
+    pub fn test_stream_error() {
+        let input = "Game 1: 1 red\nGame 2: 3green\n";
+        stream(input).for_each(|game| game.cubes().for_each(drop));
+    }
+
+    #[test]
+    pub fn test_stream_numbers() {
+        // the full parser only takes digits, so the stream shouldn't accept a sign either
+        for input in [
+            "Game 1: +3 red",
+            "Game 1: 3 red, red",
+            "Game +1: 3 red",
+            "Game : 3 red",
+        ] {
+            let streamed = std::panic::catch_unwind(|| {
+                stream(input).for_each(|game| game.cubes().for_each(drop));
+            });
+            assert!(streamed.is_err(), "{input:?}");
+        }
+    }
+
+    /// The puzzle input is 100 games, so repeat the example to that size, numbering the games
+    /// from 1 to 100
+    fn bench_input() -> String {
+        let games = include_str!("test-input").lines().cycle().take(100);
+        (1..)
+            .zip(games)
+            .map(|(id, game)| {
+                let (_, reveals) = game.split_once(": ").unwrap();
+                format!("Game {id}: {reveals}\n")
+            })
+            .collect()
+    }
+
+    /// The old implementation of part 2, on top of [parse]
+    fn part2_parse(input: &str) -> u64 {
+        let games = parse(input);
+
+        // the fewest cubes of each color, or None for colors the game never showed
+        let mut fewest: Vec<Option<u64>> = vec![None; games.colors.len()];
+
+        games
+            .games
+            .iter()
+            .map(|game| {
+                fewest.fill(None);
+                for r in &game.revealed {
+                    for &(color, count) in r {
+                        let e = &mut fewest[color as usize];
+                        *e = Some(max(e.unwrap_or(0), count));
+                    }
+                }
+
+                let power: u64 = fewest.iter().flatten().copied().product_ck();
+                power
+            })
+            .sum_ck()
+    }
+
+    #[bench]
+    fn bench_part1_stream(b: &mut Bencher) {
+        let input = bench_input();
+        let bag = Bag::default();
+        b.iter(|| possible_ids(black_box(&input), &bag));
+    }
+
+    #[bench]
+    fn bench_part1_parse(b: &mut Bencher) {
+        let input = bench_input();
+        let bag = Bag::default();
+        b.iter(|| report(&parse(black_box(&input)), &bag).total());
+    }
+
+    #[bench]
+    fn bench_part2_stream(b: &mut Bencher) {
+        let input = bench_input();
+        b.iter(|| part2(black_box(&input)));
+    }
+
+    #[bench]
+    fn bench_part2_parse(b: &mut Bencher) {
+        let input = bench_input();
+        b.iter(|| part2_parse(black_box(&input)));
+    }
 }
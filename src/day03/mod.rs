@@ -1,3 +1,4 @@
+use crate::util::checked::{self, CheckedIter};
 use std::{collections::HashMap, iter};
 
 #[derive(Debug)]
@@ -54,6 +55,7 @@ fn neighbors(x1: usize, x2: usize, y: usize) -> impl Iterator<Item = (usize, usi
 }
 
 pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
     let data = parse(input);
 
     data.iter()
@@ -70,10 +72,11 @@ pub fn part1(input: &str) -> u64 {
 
             None
         })
-        .sum()
+        .sum_ck()
 }
 
 pub fn part2(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 2);
     let data = parse(input);
 
     // map coordinates of gears to the neighboring numbers
@@ -94,8 +97,8 @@ pub fn part2(input: &str) -> u64 {
     gear_neighbors
         .values()
         .filter(|n| n.len() >= 2)
-        .map(|n| n.iter().product::<u64>())
-        .sum()
+        .map(|n| n.iter().copied().product_ck())
+        .sum_ck()
 }
 
 #[cfg(test)]
@@ -1,19 +1,25 @@
-use crate::util::{parse_u64, HashMap, HashSet};
+use crate::util::{
+    bitset::BitSet128,
+    checked::{self, CheckedIter, CheckedOps},
+    parse_u64,
+    prefix_sum::DifferenceArray,
+    simd::find_byte,
+};
 
 #[derive(Debug)]
 pub struct Card {
-    winning_numbers: HashSet<u64>,
-    numbers: HashSet<u64>,
+    winning_numbers: BitSet128,
+    numbers: BitSet128,
 }
 
 pub fn parse(input: &str) -> Vec<Card> {
     let input = input.as_bytes();
 
     // Length of an entire line. All lines have the same length.
-    let line_len = 1 + input.iter().position(|&b| b == b'\n').unwrap();
+    let line_len = 1 + find_byte(input, b'\n').unwrap();
 
     // length of the "Game x: " prefix
-    let prefix_len = 1 + input.iter().position(|&b| b == b':').unwrap();
+    let prefix_len = 1 + find_byte(input, b':').unwrap();
 
     input
         .chunks(line_len)
@@ -21,18 +27,18 @@ pub fn parse(input: &str) -> Vec<Card> {
             let line = &line[prefix_len..line_len - 1];
             let mut raw_nums = line.split(|&b| b == b' ').filter(|b| b != b"");
 
-            let mut winning_numbers: HashSet<u64> = Default::default();
-            let mut numbers: HashSet<u64> = Default::default();
+            let mut winning_numbers = BitSet128::new();
+            let mut numbers = BitSet128::new();
 
             for n in raw_nums.by_ref() {
                 if n == b"|" {
                     break;
                 }
-                winning_numbers.insert(parse_u64(n).unwrap());
+                winning_numbers.insert(parse_u64(n).unwrap() as usize);
             }
 
             for n in raw_nums {
-                numbers.insert(parse_u64(n).unwrap());
+                numbers.insert(parse_u64(n).unwrap() as usize);
             }
 
             Card {
@@ -44,38 +50,42 @@ pub fn parse(input: &str) -> Vec<Card> {
 }
 
 pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
     let cards = parse(input);
 
     cards
         .into_iter()
         .map(|card| {
-            let winning_count = card.numbers.intersection(&card.winning_numbers).count() as u64;
+            let winning_count = card.numbers.intersection_count(&card.winning_numbers) as u32;
             // 0 1 2 4 8
-            (1 << winning_count) >> 1
+            1u64.shl_ck(winning_count) >> 1
         })
-        .sum()
+        .sum_ck()
 }
 
 pub fn part2(input: &str) -> usize {
+    let _ctx = checked::context(module_path!(), 2);
     let cards = parse(input);
 
-    // map from card index to copies count
-    let mut extra_copies: HashMap<usize, usize> = Default::default();
+    // number of extra copies we've won of each card, as we go through them
+    let mut extra_copies = DifferenceArray::new(cards.len());
+    let mut extra_copies_of_this_card: i64 = 0;
 
     // total number of scratchcards we've got
-    let mut total_cards = 0;
+    let mut total_cards: i64 = 0;
 
-    for (i, card) in cards.into_iter().enumerate() {
-        let winning_count = card.numbers.intersection(&card.winning_numbers).count();
+    for (i, card) in cards.iter().enumerate() {
+        let winning_count = card.numbers.intersection_count(&card.winning_numbers);
 
-        let copies_of_this_card = *extra_copies.entry(i).or_default() + 1;
-        total_cards += copies_of_this_card;
-        for j in ((i + 1)..).take(winning_count) {
-            *extra_copies.entry(j).or_default() += copies_of_this_card;
-        }
+        extra_copies_of_this_card = extra_copies_of_this_card.add_ck(extra_copies.delta(i));
+        let copies_of_this_card = extra_copies_of_this_card.add_ck(1);
+        total_cards = total_cards.add_ck(copies_of_this_card);
+
+        let won = (i + 1)..(i + 1 + winning_count).min(cards.len());
+        extra_copies.add(won, copies_of_this_card);
     }
 
-    total_cards
+    total_cards.cast_ck()
 }
 
 #[cfg(test)]
@@ -1,3 +1,7 @@
+use crate::util::{
+    checked::{self, CheckedOps},
+    parse::Parser,
+};
 use std::{
     collections::{btree_map::Entry, BTreeMap},
     iter::{self, once},
@@ -30,7 +34,7 @@ impl Mapping {
     /// Put a single value through our mapping function.
     fn map(&self, value: i64) -> i64 {
         let Offset(offset) = self.offset_at(value);
-        value + offset
+        value.add_ck(offset)
     }
 
     /// Put a range of values through our mapping function.
@@ -71,7 +75,7 @@ impl Mapping {
             // that value in `self.ranges`, so we only need to look at the end offset.
             let &(end, Offset(offset)) = points.peek()?;
 
-            let out = start + offset..end + offset;
+            let out = start.add_ck(offset)..end.add_ck(offset);
 
             Some(out)
         })
@@ -86,61 +90,66 @@ impl Mapping {
 }
 
 pub fn parse(input: &str) -> Data<'_> {
-    let mut paragraphs = input.split("\n\n");
-
-    let seeds = paragraphs.next().unwrap().trim_start_matches("seeds: ");
-    let seeds = seeds.split(' ').map(|seed| seed.parse().unwrap()).collect();
-
-    let mappings = paragraphs
-        .map(|p| {
-            let mut lines = p.lines();
-            let name = lines.next().unwrap().trim_end_matches(" map:");
-
-            let mut mappings = Mapping::default();
-            for line in lines {
-                let (dest_start, line) = line.split_once(' ').unwrap();
-                let (source_start, len) = line.split_once(' ').unwrap();
-
-                let len: i64 = len.parse().unwrap();
-                let dest_start: i64 = dest_start.parse().unwrap();
-                let source_start: i64 = source_start.parse().unwrap();
-                let source_end = source_start + len;
-                let offset = Offset(dest_start - source_start);
-
-                match mappings.ranges.entry(source_end) {
-                    Entry::Vacant(slot) => {
-                        slot.insert(offset);
-                    }
-                    Entry::Occupied(mut slot) => match slot.get() {
-                        Offset(0) => {
+    Parser::new(input)
+        .all(|p| {
+            p.lit("seeds: ")?;
+            let seeds = p.sep_by(" ", Parser::int)?;
+            p.lit("\n\n")?;
+
+            let mappings = p.blocks(|p| {
+                let name = p.take_until(" map:")?;
+                p.lit(" map:\n")?;
+
+                let ranges = p.lines(|p| {
+                    let dest_start: i64 = p.int()?;
+                    p.lit(" ")?;
+                    let source_start: i64 = p.int()?;
+                    p.lit(" ")?;
+                    let len: i64 = p.int()?;
+                    Ok((dest_start, source_start, len))
+                })?;
+
+                let mut mappings = Mapping::default();
+                for (dest_start, source_start, len) in ranges {
+                    let source_end = source_start.add_ck(len);
+                    let offset = Offset(dest_start.sub_ck(source_start));
+
+                    match mappings.ranges.entry(source_end) {
+                        Entry::Vacant(slot) => {
                             slot.insert(offset);
                         }
-                        Offset(n) => panic!("overlapping range at {n}"),
-                    },
-                }
+                        Entry::Occupied(mut slot) => match slot.get() {
+                            Offset(0) => {
+                                slot.insert(offset);
+                            }
+                            Offset(n) => panic!("overlapping range at {n}"),
+                        },
+                    }
 
-                match mappings.ranges.entry(source_start) {
-                    Entry::Occupied(_) => {} // another range ends here, this is fine.
-                    Entry::Vacant(slot) => {
-                        slot.insert(Offset(0));
+                    match mappings.ranges.entry(source_start) {
+                        Entry::Occupied(_) => {} // another range ends here, this is fine.
+                        Entry::Vacant(slot) => {
+                            slot.insert(Offset(0));
+                        }
                     }
+
+                    assert_eq!(
+                        2,
+                        mappings.ranges.range(source_start..=source_end).count(),
+                        "overlapping range at {source_start}..{source_end}"
+                    )
                 }
 
-                assert_eq!(
-                    2,
-                    mappings.ranges.range(source_start..=source_end).count(),
-                    "overlapping range at {source_start}..{source_end}"
-                )
-            }
+                Ok((name, mappings))
+            })?;
 
-            (name, mappings)
+            Ok(Data { seeds, mappings })
         })
-        .collect();
-
-    Data { seeds, mappings }
+        .unwrap_or_else(|e| panic!("{e}"))
 }
 
 pub fn part1(input: &str) -> i64 {
+    let _ctx = checked::context(module_path!(), 1);
     let data = parse(input);
     let mut seeds = data.seeds;
 
@@ -152,6 +161,7 @@ pub fn part1(input: &str) -> i64 {
 }
 
 pub fn part2(input: &str) -> i64 {
+    let _ctx = checked::context(module_path!(), 2);
     let data = parse(input);
 
     // convert the seeds to ranges of seeds
@@ -159,7 +169,7 @@ pub fn part2(input: &str) -> i64 {
         .seeds
         .into_iter()
         .array_chunks()
-        .map(|[start, length]| (start..start + length))
+        .map(|[start, length]| (start..start.add_ck(length)))
         .collect();
 
     for (_name, mappings) in &data.mappings {
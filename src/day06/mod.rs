@@ -1,3 +1,5 @@
+use crate::util::checked::{self, CheckedIter, CheckedOps};
+
 pub fn parse(input: &str) -> (&str, &str) {
     let mut lines = input.lines();
 
@@ -25,14 +27,15 @@ fn solve_race(time: f64, record: f64) -> u64 {
     let min_button_time = (time / 2.0) - ((-time / 2.0).powi(2) - record).sqrt();
     let max_button_time = (time / 2.0) + ((-time / 2.0).powi(2) - record).sqrt();
 
-    let min_button_time = min_button_time.floor() as u64 + 1;
-    let max_button_time = max_button_time.ceil() as u64 - 1;
+    let min_button_time = (min_button_time.floor() as u64).add_ck(1);
+    let max_button_time = (max_button_time.ceil() as u64).sub_ck(1);
 
     // calculate number of possible ways to win
-    1 + max_button_time - min_button_time
+    max_button_time.add_ck(1).sub_ck(min_button_time)
 }
 
 pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
     let (times, records) = parse(input);
 
     let times = times.split_whitespace().map(|s| s.parse().unwrap());
@@ -41,10 +44,11 @@ pub fn part1(input: &str) -> u64 {
     times
         .zip(records)
         .map(|(time, record)| solve_race(time, record))
-        .product()
+        .product_ck()
 }
 
 pub fn part2(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 2);
     let (time, record) = parse(input);
 
     let time = time.replace(' ', "").parse().unwrap();
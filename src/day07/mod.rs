@@ -1,19 +1,36 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use crate::util::checked::{self, CheckedIter, CheckedOps};
+use aoc_derive::CharEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, CharEnum)]
 #[repr(u8)]
 pub enum Card {
+    #[display('J')]
     Joker,
+    #[char('2')]
     Two,
+    #[char('3')]
     Three,
+    #[char('4')]
     Four,
+    #[char('5')]
     Five,
+    #[char('6')]
     Six,
+    #[char('7')]
     Seven,
+    #[char('8')]
     Eight,
+    #[char('9')]
     Nine,
+    #[char('T')]
     Ten,
+    #[char('J')]
     Jack,
+    #[char('Q')]
     Queen,
+    #[char('K')]
     King,
+    #[char('A')]
     Ace,
 }
 
@@ -112,22 +129,7 @@ pub fn parse(input: &str) -> Vec<(Hand, u64)> {
 
             let mut cards = [Card::Ace; 5];
             hand.chars()
-                .map(|c| match c {
-                    '2' => Card::Two,
-                    '3' => Card::Three,
-                    '4' => Card::Four,
-                    '5' => Card::Five,
-                    '6' => Card::Six,
-                    '7' => Card::Seven,
-                    '8' => Card::Eight,
-                    '9' => Card::Nine,
-                    'T' => Card::Ten,
-                    'J' => Card::Jack,
-                    'Q' => Card::Queen,
-                    'K' => Card::King,
-                    'A' => Card::Ace,
-                    _ => panic!("invalid card: {c:?}"),
-                })
+                .map(|c| Card::try_from(c).unwrap_or_else(|c| panic!("invalid card: {c:?}")))
                 .enumerate()
                 .for_each(|(i, c)| cards[i] = c);
 
@@ -142,15 +144,17 @@ fn sort_and_sum(mut plays: Vec<(Hand, u64)>) -> u64 {
         .iter()
         //.map(|hand| dbg!(hand))
         .enumerate()
-        .map(|(i, (_, bid))| (i + 1) as u64 * bid)
-        .sum()
+        .map(|(i, (_, bid))| (i as u64 + 1).mul_ck(*bid))
+        .sum_ck()
 }
 
 pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
     sort_and_sum(parse(input))
 }
 
 pub fn part2(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 2);
     let mut plays = parse(input);
 
     // replace jacks with jokers
@@ -165,7 +169,7 @@ pub fn part2(input: &str) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{part1, part2};
+    use super::{part1, part2, Card};
 
     #[test]
     pub fn test_part1() {
@@ -178,4 +182,13 @@ mod tests {
         let input = include_str!("test-input");
         assert_eq!(part2(input), 5905);
     }
+
+    #[test]
+    pub fn test_card_chars() {
+        assert_eq!(Card::try_from('T'), Ok(Card::Ten));
+        assert_eq!(Card::try_from(b'2'), Ok(Card::Two));
+        assert_eq!(Card::try_from('1'), Err('1'));
+        assert_eq!(Card::Joker.to_string(), "J");
+        assert_eq!(Card::try_from('J'), Ok(Card::Jack));
+    }
 }
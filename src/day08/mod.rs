@@ -1,105 +1,147 @@
-use crate::util::HashMap;
-use num::integer::lcm;
+use crate::util::{
+    checked::{self, CheckedOps},
+    intern::Interner,
+    math::align_cycles,
+    parse::Parser,
+};
+use aoc_derive::CharEnum;
 use std::iter::repeat;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, CharEnum)]
 pub enum Dir {
+    #[char('L')]
     L,
+    #[char('R')]
     R,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct NodeKind {
+    /// Node name ends with an A
+    start: bool,
+
+    /// Node name ends with a Z
+    goal: bool,
+}
+
 pub struct Map<'a> {
     instructions: Vec<Dir>,
-    nodes: HashMap<&'a str, (&'a str, &'a str)>,
+    names: Interner<'a, NodeKind>,
+
+    /// Left and right neighbors of each node, indexed by node ID
+    neighbors: Vec<[u32; 2]>,
+}
+
+impl Map<'_> {
+    /// Get the node we end up on by going in direction `d` from `node`.
+    fn step(&self, node: u32, d: Dir) -> u32 {
+        let [l, r] = self.neighbors[node as usize];
+        match d {
+            Dir::L => l,
+            Dir::R => r,
+        }
+    }
+
+    /// Walk from `start` following the instructions forever, and yield the number of steps taken
+    /// every time we arrive at a node.
+    fn walk(&self, start: u32) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let mut node = start;
+        repeat(self.instructions.iter())
+            .flatten()
+            .enumerate()
+            .map(move |(i, &d)| {
+                node = self.step(node, d);
+                (i + 1, node)
+            })
+    }
 }
 
 pub fn parse(input: &str) -> Map<'_> {
-    let mut input = input.split("\n\n");
-    let instructions = input.next().unwrap();
-    let nodes = input.next().unwrap();
-
-    let instructions = instructions
-        .chars()
-        .map(|c| match c {
-            'L' => Dir::L,
-            'R' => Dir::R,
-            _ => panic!("invalid direction: {c:?}"),
+    let (instructions, nodes) = Parser::new(input)
+        .all(|p| {
+            let instructions = p.many(Parser::char::<Dir>)?;
+            p.lit("\n\n")?;
+
+            let nodes = p.lines(|p| {
+                let node = p.alnum()?;
+                p.lit(" = (")?;
+                let left = p.alnum()?;
+                p.lit(", ")?;
+                let right = p.alnum()?;
+                p.lit(")")?;
+
+                Ok((node, [left, right]))
+            })?;
+
+            Ok((instructions, nodes))
         })
-        .collect();
-
-    let nodes = nodes
-        .lines()
-        .map(|node| {
-            let (node, neighbors) = node.split_once(" = (").unwrap();
-            let (left, right) = neighbors.trim_end_matches(')').split_once(", ").unwrap();
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    // intern all nodes before looking at the neighbors, so that the IDs line up with `nodes`
+    let mut names = Interner::new();
+    for &(node, _) in &nodes {
+        let id = names.intern_with(node, |name| NodeKind {
+            start: name.ends_with('A'),
+            goal: name.ends_with('Z'),
+        });
+        assert_eq!(id as usize + 1, names.len(), "duplicate node: {node:?}");
+    }
 
-            (node, (left, right))
+    let neighbors = nodes
+        .iter()
+        .map(|(_, neighbors)| {
+            neighbors.map(|name| {
+                names
+                    .get(name)
+                    .unwrap_or_else(|| panic!("undefined node: {name:?}"))
+            })
         })
         .collect();
 
     Map {
         instructions,
-        nodes,
+        names,
+        neighbors,
     }
 }
 
 pub fn part1(input: &str) -> usize {
-    const GOAL: &str = "ZZZ";
-    let Map {
-        instructions,
-        nodes,
-    } = parse(input);
-
-    let mut node = "AAA";
-    repeat(instructions.iter())
-        .flatten()
-        .enumerate()
-        .find_map(|(i, d)| {
-            let (l, r) = nodes[node];
-            node = match d {
-                Dir::L => l,
-                Dir::R => r,
-            };
-            dbg!(d, node);
-
-            (node == GOAL).then_some(i + 1)
-        })
-        .expect("this iterator goes on forever")
+    let _ctx = checked::context(module_path!(), 1);
+    let map = parse(input);
+    let start = map.names.get("AAA").expect("no start node");
+    let goal = map.names.get("ZZZ").expect("no goal node");
+
+    let steps = map
+        .walk(start)
+        .find_map(|(steps, node)| (node == goal).then_some(steps))
+        .expect("this iterator goes on forever");
+
+    steps
 }
 
 pub fn part2(input: &str) -> usize {
-    let Map {
-        instructions,
-        nodes,
-    } = parse(input);
+    let _ctx = checked::context(module_path!(), 2);
+    let map = parse(input);
 
-    let starting_nodes: Vec<_> = nodes
-        .keys()
-        .copied()
-        .filter(|node| &node[2..] == "A")
-        .collect();
-
-    starting_nodes
+    let cycles = map
+        .names
         .iter()
-        .map(|&starting| {
-            let mut node = starting;
-            let steps_to_z = repeat(instructions.iter())
-                .flatten()
-                .enumerate()
-                .find_map(|(i, d)| {
-                    let (l, r) = nodes[node];
-                    node = match d {
-                        Dir::L => l,
-                        Dir::R => r,
-                    };
-
-                    (&node[2..] == "Z").then_some(i + 1)
-                })
-                .expect("this iterator goes on forever");
-
-            steps_to_z
-        })
-        .fold(1, lcm)
+        .filter(|(_, _, kind)| kind.start)
+        .map(|(starting, _, _)| {
+            let mut z_hits = map
+                .walk(starting)
+                .filter_map(|(steps, node)| map.names.flags(node).goal.then_some(steps));
+
+            // Assume that the path loops back to the same Z-node, and that it's the only Z-node on
+            // the loop. The offset and period of the loop doesn't have to be equal though.
+            let first = z_hits.next().expect("this iterator goes on forever");
+            let second = z_hits.next().expect("this iterator goes on forever");
+
+            (first.cast_ck(), (second - first).cast_ck())
+        });
+
+    let steps: i64 = align_cycles(cycles).expect("ghosts never line up");
+    steps.cast_ck()
 }
 
 #[cfg(test)]
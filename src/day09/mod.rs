@@ -1,56 +1,30 @@
+use crate::util::{
+    checked::{self, CheckedIter},
+    poly::Poly,
+    scan::ints,
+};
+
 pub fn parse(input: &str) -> Vec<Vec<i64>> {
     input
         .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|n| n.parse().unwrap())
-                .collect()
-        })
+        .map(|line| ints(line).map(Result::unwrap).collect())
         .collect()
 }
 
-fn calculate_derivatives(sequence: Vec<i64>) -> Vec<Vec<i64>> {
-    let mut sequences = vec![sequence];
-    loop {
-        let last = sequences.last().unwrap();
-
-        let derivative: Vec<i64> = last.array_windows().map(|[a, b]| b - a).collect();
-
-        let all_zeroes = derivative.iter().all(|&n| n == 0);
-        sequences.push(derivative);
-
-        if all_zeroes {
-            break;
-        }
-    }
-
-    sequences
-}
-
-fn solve(input: &str, next_slope: impl Fn(&[i64], i64) -> i64) -> i64 {
-    parse(input)
-        .into_iter()
-        .map(|sequence| {
-            let derivatives = calculate_derivatives(sequence);
-            derivatives[..derivatives.len() - 1]
-                .iter()
-                .rfold(0, |slope, function| next_slope(function, slope))
-        })
-        .sum()
-}
-
 pub fn part1(input: &str) -> i64 {
-    solve(input, |function, slope| {
-        let &prev_value = function.last().unwrap();
-        prev_value + slope // calculate next value
-    })
+    let _ctx = checked::context(module_path!(), 1);
+    parse(input)
+        .iter()
+        .map(|sequence| Poly::fit(sequence).eval(sequence.len() as i64)) // calculate next value
+        .sum_ck()
 }
 
 pub fn part2(input: &str) -> i64 {
-    solve(input, |function, slope| {
-        let &next_value = function.first().unwrap();
-        next_value - slope // calculate previous value
-    })
+    let _ctx = checked::context(module_path!(), 2);
+    parse(input)
+        .iter()
+        .map(|sequence| Poly::fit(sequence).eval(-1)) // calculate previous value
+        .sum_ck()
 }
 
 #[cfg(test)]
@@ -1,88 +1,66 @@
-use crate::util::{HashMap, HashSet};
+use crate::util::{
+    bitset::BitSet,
+    checked::{self, CheckedIter, CheckedOps},
+    combinatorics::pairs,
+    grid::SignedPos as Coord,
+    prefix_sum::PrefixSum,
+    sparse_grid::SparseGrid,
+};
+use std::ops::RangeInclusive;
 
-pub type Coord = (i64, i64);
+pub type Galaxies = SparseGrid<()>;
 
-pub fn parse(input: &str) -> HashSet<Coord> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.as_bytes()
-                .iter()
-                .enumerate()
-                .filter(|&(_, &c)| c == b'#')
-                .map(move |(x, _)| (x as i64, y as i64))
-        })
-        .collect()
+pub fn parse(input: &str) -> Galaxies {
+    SparseGrid::parse(input, |c| (c == b'#').then_some(()))
 }
 
 pub fn part1(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 1);
     let galaxies = parse(input);
     expand_galaxies(galaxies, 1)
 }
 
 pub fn part2(input: &str) -> u64 {
+    let _ctx = checked::context(module_path!(), 2);
     let galaxies = parse(input);
     expand_galaxies(galaxies, 999999)
 }
 
-fn expand_galaxies(mut galaxies: HashSet<Coord>, expansion: i64) -> u64 {
-    let min_x = 0;
-    let min_y = 0;
-    let max_x = galaxies.iter().map(|&(x, _)| x).max().unwrap();
-    let max_y = galaxies.iter().map(|&(_, y)| y).max().unwrap();
+fn expand_galaxies(galaxies: Galaxies, expansion: i64) -> u64 {
+    let bounds = galaxies.bounds().expect("no galaxies");
 
-    // expand universe in x direction
-    let mut expand = vec![];
-    for column in min_x + 1..max_x {
-        if galaxies.iter().all(|&(x, _)| x != column) {
-            expand.push(column);
-        }
-    }
-    for column in expand.into_iter().rev() {
-        galaxies = galaxies
-            .drain()
-            .map(|(x, y)| {
-                if x > column {
-                    (x + expansion, y)
-                } else {
-                    (x, y)
-                }
-            })
-            .collect();
-    }
+    // count the empty columns and rows, so that we can tell how many come before a galaxy
+    let empty = |occupied: BitSet, range: RangeInclusive<i64>| {
+        PrefixSum::new(range.map(|i| i64::from(!occupied.contains(i as usize))))
+    };
+    let empty_columns = empty(
+        galaxies.positions().map(|(x, _)| x as usize).collect(),
+        bounds.xs(),
+    );
+    let empty_rows = empty(
+        galaxies.positions().map(|(_, y)| y as usize).collect(),
+        bounds.ys(),
+    );
 
-    // expand universe in y direction
-    let mut expand = vec![];
-    for row in min_y + 1..max_y {
-        if galaxies.iter().all(|&(_, y)| y != row) {
-            expand.push(row);
-        }
-    }
-    for row in expand.into_iter().rev() {
-        galaxies = galaxies
-            .drain()
-            .map(|(x, y)| if y > row { (x, y + expansion) } else { (x, y) })
-            .collect();
-    }
+    // every empty column or row before a coordinate pushes it `expansion` steps further out
+    let expand = |n: i64, min: i64, empty: &PrefixSum<i64>| {
+        let empty_before = empty.sum(..(n - min) as usize);
+        n.add_ck(expansion.mul_ck(empty_before))
+    };
 
     // calculate distances between pairs
-    let mut pair_distances: HashMap<[Coord; 2], u64> = HashMap::default();
-    for &c1 @ (x1, y1) in galaxies.iter() {
-        for &c2 @ (x2, y2) in galaxies.iter() {
-            if c1 == c2 {
-                continue;
-            }
-
-            let mut pair = [c1, c2];
-            pair.sort();
-
-            let distance = x1.abs_diff(x2) + y1.abs_diff(y2);
-            pair_distances.insert(pair, distance);
-        }
-    }
-
-    pair_distances.values().sum()
+    let galaxies: Vec<Coord> = galaxies
+        .positions()
+        .map(|(x, y)| {
+            (
+                expand(x, bounds.min.0, &empty_columns),
+                expand(y, bounds.min.1, &empty_rows),
+            )
+        })
+        .collect();
+    pairs(&galaxies)
+        .map(|(&(x1, y1), &(x2, y2))| x1.abs_diff(x2).add_ck(y1.abs_diff(y2)))
+        .sum_ck()
 }
 
 #[cfg(test)]
@@ -1,7 +1,8 @@
 #![feature(test)]
 #![feature(iter_array_chunks, array_chunks, array_windows)]
-#![feature(iter_advance_by, slice_partition_dedup)]
+#![feature(iter_advance_by, slice_partition_dedup, array_try_from_fn)]
 #![feature(binary_heap_drain_sorted, btree_cursors)]
+#![feature(portable_simd)]
 extern crate test;
 
 mod util;
@@ -1,41 +1,165 @@
 //! Random utility functions that might be useful for thing
 #![allow(dead_code)]
 
-use std::hash::BuildHasherDefault;
+pub mod aho_corasick;
+pub mod bitset;
+pub mod checked;
+pub mod combinatorics;
+pub mod cycle;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod intern;
+pub mod linalg;
+pub mod math;
+pub mod memo;
+pub mod options;
+pub mod parse;
+pub mod poly;
+pub mod prefix_sum;
+pub mod scan;
+pub mod simd;
+pub mod sparse_grid;
+pub mod union_find;
+
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasherDefault, Hash},
+};
 
 use hashers::fx_hash::FxHasher;
 
 pub type HashMap<K, V> = std::collections::HashMap<K, V, BuildHasherDefault<FxHasher>>;
 pub type HashSet<T> = std::collections::HashSet<T, BuildHasherDefault<FxHasher>>;
 
-/// Return mutable references to two different elements in a slice
-pub fn get_2_mut<T>(slice: &mut [T], first: usize, second: usize) -> [&mut T; 2] {
-    assert!(first < slice.len());
-    assert!(second < slice.len());
-    assert!(first != second);
+/// Error returned by [get_many_mut] and friends
+///
+/// The payload is always the position of the offending request in the array that was passed in,
+/// whether that's an index, a key or a grid position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetManyMutError {
+    /// The requested element doesn't exist.
+    OutOfBounds(usize),
+
+    /// The element was already requested at an earlier position.
+    Duplicate(usize),
+}
+
+/// Return mutable references to `N` different elements in a slice
+pub fn get_many_mut<T, const N: usize>(
+    slice: &mut [T],
+    indices: [usize; N],
+) -> Result<[&mut T; N], GetManyMutError> {
+    for (i, &index) in indices.iter().enumerate() {
+        if index >= slice.len() {
+            return Err(GetManyMutError::OutOfBounds(i));
+        }
+        if indices[..i].contains(&index) {
+            return Err(GetManyMutError::Duplicate(i));
+        }
+    }
 
     let slice = slice.as_mut_ptr();
-    // SAFETY: first and second are within bounds, and do not refer to the same element
-    unsafe {
-        [
-            slice.add(first).as_mut().unwrap(),
-            slice.add(second).as_mut().unwrap(),
-        ]
+    // SAFETY: all indices are within bounds, and none of them refer to the same element
+    Ok(indices.map(|i| unsafe { &mut *slice.add(i) }))
+}
+
+/// Return mutable references to the values of `N` different keys in a map
+pub fn get_many_mut_map<'a, K, V, Q, const N: usize>(
+    map: &'a mut HashMap<K, V>,
+    keys: [&Q; N],
+) -> Result<[&'a mut V; N], GetManyMutError>
+where
+    K: Borrow<Q> + Hash + Eq,
+    Q: Hash + Eq + ?Sized,
+{
+    // get_disjoint_mut panics on duplicate keys, so check for them first to return an error
+    for (i, key) in keys.iter().enumerate() {
+        if keys[..i].contains(key) {
+            return Err(GetManyMutError::Duplicate(i));
+        }
+    }
+
+    let values = map.get_disjoint_mut(keys);
+    if let Some(i) = values.iter().position(Option::is_none) {
+        return Err(GetManyMutError::OutOfBounds(i));
     }
+    Ok(values.map(|value| value.expect("checked above")))
 }
 
 /// Parse a u64 from an ascii string. Slightly faster than FromStr.
+///
+/// Returns `None` if the string contains anything but digits, or if the number overflows.
 pub fn parse_u64(b: impl AsRef<[u8]>) -> Option<u64> {
-    let b = b.as_ref();
+    b.as_ref().iter().try_fold(0u64, |n, &b| {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        n.checked_mul(10)?.checked_add(u64::from(b - b'0'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    // These tests exercise unsafe code, and are kept small enough to run under miri:
+    // cargo miri test util::tests
+    use super::*;
+
+    #[test]
+    pub fn test_get_many_mut() {
+        let mut v = [1, 2, 3, 4];
+        let [a, b, c] = get_many_mut(&mut v, [3, 0, 1]).unwrap();
+        *a += 10;
+        *b += 20;
+        *c += 30;
+        assert_eq!(v, [21, 32, 3, 14]);
+
+        assert_eq!(get_many_mut::<_, 0>(&mut v, []), Ok([]));
+        assert_eq!(
+            get_many_mut(&mut v, [0, 4]),
+            Err(GetManyMutError::OutOfBounds(1))
+        );
+        assert_eq!(
+            get_many_mut(&mut v, [3, 1, 3]),
+            Err(GetManyMutError::Duplicate(2))
+        );
+    }
+
+    #[test]
+    pub fn test_get_many_mut_map() {
+        let mut map: HashMap<String, u32> = HashMap::default();
+        map.insert("a".into(), 1);
+        map.insert("b".into(), 2);
+        map.insert("c".into(), 3);
+
+        let [a, c] = get_many_mut_map(&mut map, ["a", "c"]).unwrap();
+        std::mem::swap(a, c);
+        assert_eq!(map["a"], 3);
+        assert_eq!(map["c"], 1);
 
-    let mut out = 0;
-    if b.iter().any(|b| !b.is_ascii_digit()) {
-        return None;
+        assert_eq!(
+            get_many_mut_map(&mut map, ["a", "x"]),
+            Err(GetManyMutError::OutOfBounds(1))
+        );
+        assert_eq!(
+            get_many_mut_map(&mut map, ["b", "c", "b"]),
+            Err(GetManyMutError::Duplicate(2))
+        );
     }
 
-    for (i, &b) in b.iter().rev().enumerate() {
-        let n = u64::from(b - b'0');
-        out += n * (10u64.pow(i as u32));
+    #[test]
+    pub fn test_get_many_mut_map_aliasing() {
+        // enough keys that the map has to probe past other buckets, and writes through every
+        // reference after they've all been created, so that miri catches any aliasing
+        let mut map: HashMap<u32, u32> = (0..64).map(|i| (i, i)).collect();
+        let keys = [&3, &17, &42, &63, &0];
+        let values = get_many_mut_map(&mut map, keys).unwrap();
+        for value in values {
+            *value += 100;
+        }
+        for key in keys {
+            assert_eq!(map[key], key + 100);
+        }
+        assert_eq!(map[&1], 1);
     }
-    Some(out)
 }
@@ -0,0 +1,240 @@
+//! Aho–Corasick automaton, for finding many (possibly overlapping) patterns in a single pass
+
+use std::collections::VecDeque;
+
+/// A state with no transition for a byte. Only used while building.
+const MISSING: u32 = u32::MAX;
+
+/// Finds all occurrences of a set of byte patterns, each with an associated value
+///
+/// The automaton is a DFA over bytes, so UTF-8 patterns and haystacks work as expected.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick<V> {
+    /// `transitions[state * 256 + byte]` is the next state. State 0 is the root.
+    transitions: Vec<u32>,
+
+    /// The patterns that end in each state, longest first. The patterns for `state` are
+    /// `outputs[output_start[state]..output_start[state + 1]]`.
+    outputs: Vec<u32>,
+    output_start: Vec<u32>,
+
+    /// Length and value of each pattern.
+    patterns: Vec<(usize, V)>,
+}
+
+/// An occurrence of a pattern, see [AhoCorasick::find_overlapping]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Match<'a, V> {
+    /// Byte offset of the start of the match.
+    pub start: usize,
+
+    /// Byte offset of the end of the match, exclusive.
+    pub end: usize,
+
+    /// Value of the pattern that matched.
+    pub value: &'a V,
+}
+
+// derive would require V: Copy
+impl<V> Clone for Match<'_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for Match<'_, V> {}
+
+impl<V> Match<'_, V> {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl<V> AhoCorasick<V> {
+    /// Build an automaton from a list of `(pattern, value)`. Empty patterns are ignored.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = (P, V)>) -> Self {
+        let mut transitions = vec![MISSING; 256];
+        let mut outputs: Vec<Vec<u32>> = vec![vec![]];
+        let mut lengths_and_values = vec![];
+
+        // build a trie of all patterns
+        for (id, (pattern, value)) in (0..).zip(patterns) {
+            let pattern = pattern.as_ref();
+            lengths_and_values.push((pattern.len(), value));
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let mut state = 0;
+            for &b in pattern {
+                let next = &mut transitions[state * 256 + b as usize];
+                if *next == MISSING {
+                    *next = outputs.len() as u32;
+                    outputs.push(vec![]);
+                    transitions.extend([MISSING; 256]);
+                }
+                state = transitions[state * 256 + b as usize] as usize;
+            }
+            outputs[state].push(id);
+        }
+
+        // the longest pattern ending in a state is the one spelled out by the trie path
+        let patterns = lengths_and_values;
+        for output in &mut outputs {
+            output.sort_by_key(|&id| std::cmp::Reverse(patterns[id as usize].0));
+        }
+
+        // breadth first, so that the failure state (the longest proper suffix that's also in the
+        // trie) is always done before the states that fail to it
+        let mut fail = vec![0u32; outputs.len()];
+        let mut queue = VecDeque::new();
+        for next in &mut transitions[..256] {
+            match *next {
+                MISSING => *next = 0,
+                _ => queue.push_back(*next as usize),
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[fail[state] as usize].clone();
+            outputs[state].extend(inherited);
+
+            for b in 0..256 {
+                let fallback = transitions[fail[state] as usize * 256 + b];
+                match transitions[state * 256 + b] {
+                    MISSING => transitions[state * 256 + b] = fallback,
+                    next => {
+                        fail[next as usize] = fallback;
+                        queue.push_back(next as usize);
+                    }
+                }
+            }
+        }
+
+        let mut output_start = vec![0];
+        for output in &outputs {
+            output_start.push(output_start[output_start.len() - 1] + output.len() as u32);
+        }
+
+        AhoCorasick {
+            transitions,
+            outputs: outputs.concat(),
+            output_start,
+            patterns,
+        }
+    }
+
+    /// The patterns that end in `state`, longest first.
+    fn outputs(&self, state: u32) -> &[u32] {
+        let state = state as usize;
+        &self.outputs[self.output_start[state] as usize..self.output_start[state + 1] as usize]
+    }
+
+    /// Number of patterns, including empty ones.
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Find all matches, including overlapping ones.
+    ///
+    /// Matches are yielded in order of their end. Matches with the same end are yielded longest
+    /// first.
+    pub fn find_overlapping<'a>(
+        &'a self,
+        haystack: &'a [u8],
+    ) -> impl Iterator<Item = Match<'a, V>> + 'a {
+        haystack
+            .iter()
+            .scan(0, |state, &b| {
+                *state = self.transitions[*state as usize * 256 + b as usize];
+                Some(*state)
+            })
+            .enumerate()
+            .flat_map(move |(i, state)| {
+                self.outputs(state).iter().map(move |&id| {
+                    let (len, value) = &self.patterns[id as usize];
+                    Match {
+                        start: i + 1 - len,
+                        end: i + 1,
+                        value,
+                    }
+                })
+            })
+    }
+
+    /// Find the first and the last match in a single pass.
+    ///
+    /// The first match is the one that ends first, and the last match is the one that starts
+    /// last. Ties are broken in favor of the longer match.
+    pub fn first_last<'a>(&'a self, haystack: &'a [u8]) -> Option<(Match<'a, V>, Match<'a, V>)> {
+        // this is the same as going through find_overlapping, but it's hot enough that the plain
+        // loop makes a difference
+        let mut state = 0;
+        let mut first_last: Option<(Match<V>, Match<V>)> = None;
+        for (i, &b) in haystack.iter().enumerate() {
+            state = self.transitions[state as usize * 256 + b as usize];
+            for &id in self.outputs(state) {
+                let (len, value) = &self.patterns[id as usize];
+                let m = Match {
+                    start: i + 1 - len,
+                    end: i + 1,
+                    value,
+                };
+                match &mut first_last {
+                    None => first_last = Some((m, m)),
+                    Some((_, last)) if (m.start, m.len()) > (last.start, last.len()) => *last = m,
+                    Some(_) => {}
+                }
+            }
+        }
+        first_last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans<V: Copy>(ac: &AhoCorasick<V>, haystack: &str) -> Vec<(usize, usize, V)> {
+        ac.find_overlapping(haystack.as_bytes())
+            .map(|m| (m.start, m.end, *m.value))
+            .collect()
+    }
+
+    #[test]
+    pub fn test_overlapping() {
+        let ac = AhoCorasick::new([("he", 0), ("she", 1), ("his", 2), ("hers", 3)]);
+        assert_eq!(spans(&ac, "ushers"), [(1, 4, 1), (2, 4, 0), (2, 6, 3)]);
+        assert_eq!(spans(&ac, "ahishe"), [(1, 4, 2), (3, 6, 1), (4, 6, 0)]);
+        assert_eq!(spans(&ac, "xyz"), []);
+    }
+
+    #[test]
+    pub fn test_first_last() {
+        let ac = AhoCorasick::new([("one", 1), ("two", 2), ("eight", 8), ("1", 1)]);
+        let first_last = |s: &str| {
+            ac.first_last(s.as_bytes())
+                .map(|(first, last)| (*first.value, *last.value))
+        };
+        assert_eq!(first_last("xtwone1x"), Some((2, 1)));
+        assert_eq!(first_last("eightwo"), Some((8, 2)));
+        assert_eq!(first_last("zoneight"), Some((1, 8)));
+        assert_eq!(first_last("nope"), None);
+
+        // "bc" ends before "abcd", and starts after it, so it's both the first and the last match
+        let ac = AhoCorasick::new([("abcd", 'x'), ("bc", 'y'), ("", 'z')]);
+        let (first, last) = ac.first_last(b"abcd").unwrap();
+        assert_eq!((*first.value, *last.value), ('y', 'y'));
+        assert_eq!(ac.pattern_count(), 3);
+    }
+
+    #[test]
+    pub fn test_unicode() {
+        let ac = AhoCorasick::new([("ett", 1), ("två", 2), ("tre", 3), ("åtta", 8)]);
+        assert_eq!(spans(&ac, "tvåtta"), [(0, 4, 2), (2, 7, 8)]);
+    }
+}
@@ -0,0 +1,379 @@
+//! Sets of small integers, stored as bits
+
+use std::iter::FusedIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+/// A set of integers in the range `0..128`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet128(u128);
+
+/// A set of integers in the range `0..64*N`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitArray<const N: usize>([u64; N]);
+
+/// A growable set of integers, sized after the largest element
+#[derive(Default, Debug, Clone)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+/// Iterator over the bits set in a word, as indices offset by `base`
+#[derive(Clone)]
+struct WordBits {
+    word: u64,
+    base: usize,
+}
+
+impl Iterator for WordBits {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.word == 0 {
+            return None;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1; // clear lowest set bit
+        Some(self.base + bit)
+    }
+}
+
+impl FusedIterator for WordBits {}
+
+/// Iterate over the set bits of a slice of words
+fn iter_words(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &word)| WordBits { word, base: i * 64 })
+}
+
+impl BitSet128 {
+    pub const fn new() -> Self {
+        BitSet128(0)
+    }
+
+    /// Add a value to the set. Returns whether the value was newly inserted.
+    pub fn insert(&mut self, n: usize) -> bool {
+        assert!(n < 128, "{n} is too big for a BitSet128");
+        let had = self.contains(n);
+        self.0 |= 1 << n;
+        !had
+    }
+
+    /// Remove a value from the set. Returns whether the value was present.
+    pub fn remove(&mut self, n: usize) -> bool {
+        let had = self.contains(n);
+        if had {
+            self.0 &= !(1 << n);
+        }
+        had
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        n < 128 && self.0 & (1 << n) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Count the elements in `self ∩ other`, without building the intersection.
+    pub fn intersection_count(&self, other: &Self) -> usize {
+        (self.0 & other.0).count_ones() as usize
+    }
+
+    /// Iterate over the values in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let low = WordBits {
+            word: self.0 as u64,
+            base: 0,
+        };
+        let high = WordBits {
+            word: (self.0 >> 64) as u64,
+            base: 64,
+        };
+        low.chain(high)
+    }
+}
+
+impl<const N: usize> Default for BitArray<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> BitArray<N> {
+    pub const fn new() -> Self {
+        BitArray([0; N])
+    }
+
+    /// Add a value to the set. Returns whether the value was newly inserted.
+    pub fn insert(&mut self, n: usize) -> bool {
+        assert!(n < 64 * N, "{n} is too big for a BitArray<{N}>");
+        let had = self.contains(n);
+        self.0[n / 64] |= 1 << (n % 64);
+        !had
+    }
+
+    /// Remove a value from the set. Returns whether the value was present.
+    pub fn remove(&mut self, n: usize) -> bool {
+        let had = self.contains(n);
+        if had {
+            self.0[n / 64] &= !(1 << (n % 64));
+        }
+        had
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        n < 64 * N && self.0[n / 64] & (1 << (n % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    /// Count the elements in `self ∩ other`, without building the intersection.
+    pub fn intersection_count(&self, other: &Self) -> usize {
+        (self.0.iter().zip(&other.0))
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterate over the values in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        iter_words(&self.0)
+    }
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty set with room for values in `0..n` without reallocating.
+    pub fn with_capacity(n: usize) -> Self {
+        BitSet {
+            words: vec![0; n.div_ceil(64)],
+        }
+    }
+
+    /// Add a value to the set. Returns whether the value was newly inserted.
+    pub fn insert(&mut self, n: usize) -> bool {
+        let word = n / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let had = self.contains(n);
+        self.words[word] |= 1 << (n % 64);
+        !had
+    }
+
+    /// Remove a value from the set. Returns whether the value was present.
+    pub fn remove(&mut self, n: usize) -> bool {
+        let had = self.contains(n);
+        if had {
+            self.words[n / 64] &= !(1 << (n % 64));
+        }
+        had
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        match self.words.get(n / 64) {
+            Some(word) => word & (1 << (n % 64)) != 0,
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Count the elements in `self ∩ other`, without building the intersection.
+    pub fn intersection_count(&self, other: &Self) -> usize {
+        (self.words.iter().zip(&other.words))
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterate over the values in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        iter_words(&self.words)
+    }
+
+    /// Make sure we have at least as many words as `other`, so that the set operations can work
+    /// word-by-word.
+    fn grow_to(&mut self, other: &Self) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+    }
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        // ignore trailing empty words
+        let (short, long) = if self.words.len() < other.words.len() {
+            (&self.words, &other.words)
+        } else {
+            (&other.words, &self.words)
+        };
+        long[..short.len()] == short[..] && long[short.len()..].iter().all(|&w| w == 0)
+    }
+}
+
+impl Eq for BitSet {}
+
+impl FromIterator<usize> for BitSet128 {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|n| {
+            set.insert(n);
+        });
+        set
+    }
+}
+
+impl<const N: usize> FromIterator<usize> for BitArray<N> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|n| {
+            set.insert(n);
+        });
+        set
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|n| {
+            set.insert(n);
+        });
+        set
+    }
+}
+
+/// Implement a set operator, and its assigning version, for the bitset types.
+///
+/// The last argument is the operation on a single word, e.g. `|a, b| a & !b` for set difference.
+macro_rules! impl_set_op {
+    ($Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident, |$a:ident, $b:ident| $word_op:expr) => {
+        impl $OpAssign for BitSet128 {
+            fn $op_assign(&mut self, rhs: Self) {
+                let ($a, $b) = (self.0, rhs.0);
+                self.0 = $word_op;
+            }
+        }
+
+        impl<const N: usize> $OpAssign for BitArray<N> {
+            fn $op_assign(&mut self, rhs: Self) {
+                for (word, $b) in self.0.iter_mut().zip(rhs.0) {
+                    let $a = *word;
+                    *word = $word_op;
+                }
+            }
+        }
+
+        impl $OpAssign<&BitSet> for BitSet {
+            fn $op_assign(&mut self, rhs: &BitSet) {
+                self.grow_to(rhs);
+                let rhs = rhs.words.iter().copied().chain(std::iter::repeat(0));
+                for (word, $b) in self.words.iter_mut().zip(rhs) {
+                    let $a = *word;
+                    *word = $word_op;
+                }
+            }
+        }
+
+        impl $Op for BitSet128 {
+            type Output = Self;
+
+            fn $op(mut self, rhs: Self) -> Self {
+                self.$op_assign(rhs);
+                self
+            }
+        }
+
+        impl<const N: usize> $Op for BitArray<N> {
+            type Output = Self;
+
+            fn $op(mut self, rhs: Self) -> Self {
+                self.$op_assign(rhs);
+                self
+            }
+        }
+
+        impl $Op for &BitSet {
+            type Output = BitSet;
+
+            fn $op(self, rhs: Self) -> BitSet {
+                let mut out = self.clone();
+                out.$op_assign(rhs);
+                out
+            }
+        }
+    };
+}
+
+impl_set_op!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b);
+impl_set_op!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b);
+impl_set_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a ^ b);
+impl_set_op!(Sub, sub, SubAssign, sub_assign, |a, b| a & !b);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_bitset128() {
+        let a: BitSet128 = [1, 5, 64, 127].into_iter().collect();
+        let b: BitSet128 = [5, 6, 127].into_iter().collect();
+
+        assert_eq!(a.len(), 4);
+        assert!(a.contains(64));
+        assert!(!a.contains(128));
+        assert_eq!(a.intersection_count(&b), 2);
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), [5, 127]);
+        assert_eq!((a | b).len(), 5);
+        assert_eq!((a ^ b).iter().collect::<Vec<_>>(), [1, 6, 64]);
+        assert_eq!((a - b).iter().collect::<Vec<_>>(), [1, 64]);
+    }
+
+    #[test]
+    pub fn test_bit_array() {
+        let mut a: BitArray<3> = [0, 100, 191].into_iter().collect();
+        let b: BitArray<3> = [100, 150].into_iter().collect();
+
+        assert_eq!(a.intersection_count(&b), 1);
+        assert!(a.remove(0));
+        assert!(!a.remove(0));
+        a |= b;
+        assert_eq!(a.iter().collect::<Vec<_>>(), [100, 150, 191]);
+    }
+
+    #[test]
+    pub fn test_bitset() {
+        let mut a: BitSet = [3, 1000].into_iter().collect();
+        let b: BitSet = [3, 4].into_iter().collect();
+
+        assert_eq!((&a & &b).iter().collect::<Vec<_>>(), [3]);
+        assert_eq!((&b - &a).iter().collect::<Vec<_>>(), [4]);
+        assert_eq!((&b | &a).len(), 3);
+
+        // equality ignores capacity
+        a.remove(1000);
+        assert_eq!(a, [3].into_iter().collect());
+    }
+}
@@ -0,0 +1,209 @@
+//! Overflow-checked arithmetic for solvers
+//!
+//! When built with `--features checked`, the helpers in here panic on overflow, with a message
+//! saying which day, part and operation overflowed. Otherwise they compile down to plain
+//! arithmetic, which wraps silently in release builds.
+//!
+//! Solvers set the day and part for the error messages by calling [context] at the start:
+//!
+//! ```ignore
+//! pub fn part1(input: &str) -> u64 {
+//!     let _ctx = checked::context(module_path!(), 1);
+//!     parse(input).map(|(a, b)| a.mul_ck(b)).sum_ck()
+//! }
+//! ```
+
+use std::any::type_name;
+use std::cell::Cell;
+use std::fmt::Display;
+
+thread_local! {
+    /// The (day, part) that is currently running on this thread
+    static CONTEXT: Cell<(&'static str, u8)> = const { Cell::new(("unknown day", 0)) };
+}
+
+/// Guard returned by [context]. Restores the previous context when dropped.
+#[must_use = "the context is reset when this is dropped"]
+pub struct Context {
+    previous: (&'static str, u8),
+}
+
+/// Set which day and part is running, for overflow error messages.
+///
+/// `module` is the module path of the day, i.e. `module_path!()`.
+pub fn context(module: &'static str, part: u8) -> Context {
+    let day = module.rsplit("::").next().unwrap_or(module);
+    Context {
+        previous: CONTEXT.replace((day, part)),
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        CONTEXT.set(self.previous);
+    }
+}
+
+#[cold]
+#[track_caller]
+fn overflow(operation: std::fmt::Arguments) -> ! {
+    let (day, part) = CONTEXT.get();
+    panic!("arithmetic overflow in {day} part {part}: {operation}")
+}
+
+/// Arithmetic that panics on overflow when the `checked` feature is enabled
+pub trait CheckedOps: Sized + Copy + Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn add_ck(self, rhs: Self) -> Self;
+    fn sub_ck(self, rhs: Self) -> Self;
+    fn mul_ck(self, rhs: Self) -> Self;
+
+    /// Shift left. Bits that are shifted out count as an overflow.
+    fn shl_ck(self, rhs: u32) -> Self;
+
+    /// Convert to another integer type. Values that don't fit count as an overflow.
+    ///
+    /// Unlike the other operations, this is checked even without the `checked` feature.
+    #[track_caller]
+    fn cast_ck<T: TryFrom<Self>>(self) -> T {
+        T::try_from(self)
+            .unwrap_or_else(|_| overflow(format_args!("{self} as {}", type_name::<T>())))
+    }
+}
+
+macro_rules! impl_checked_ops {
+    ($($t:ty),*) => {$(
+        impl CheckedOps for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            #[track_caller]
+            #[inline]
+            fn add_ck(self, rhs: Self) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_add(rhs)
+                        .unwrap_or_else(|| overflow(format_args!("{self} + {rhs}")))
+                } else {
+                    self + rhs
+                }
+            }
+
+            #[track_caller]
+            #[inline]
+            fn sub_ck(self, rhs: Self) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_sub(rhs)
+                        .unwrap_or_else(|| overflow(format_args!("{self} - {rhs}")))
+                } else {
+                    self - rhs
+                }
+            }
+
+            #[track_caller]
+            #[inline]
+            fn mul_ck(self, rhs: Self) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_mul(rhs)
+                        .unwrap_or_else(|| overflow(format_args!("{self} * {rhs}")))
+                } else {
+                    self * rhs
+                }
+            }
+
+            #[track_caller]
+            #[inline]
+            fn shl_ck(self, rhs: u32) -> Self {
+                if cfg!(feature = "checked") {
+                    self.checked_shl(rhs)
+                        .filter(|shifted| shifted >> rhs == self)
+                        .unwrap_or_else(|| overflow(format_args!("{self} << {rhs}")))
+                } else {
+                    self << rhs
+                }
+            }
+        }
+    )*};
+}
+
+impl_checked_ops!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Checked versions of [Iterator::sum] and [Iterator::product]
+pub trait CheckedIter: Iterator {
+    #[track_caller]
+    fn sum_ck(self) -> Self::Item
+    where
+        Self: Sized,
+        Self::Item: CheckedOps,
+    {
+        let mut sum = Self::Item::ZERO;
+        for n in self {
+            sum = sum.add_ck(n);
+        }
+        sum
+    }
+
+    #[track_caller]
+    fn product_ck(self) -> Self::Item
+    where
+        Self: Sized,
+        Self::Item: CheckedOps,
+    {
+        let mut product = Self::Item::ONE;
+        for n in self {
+            product = product.mul_ck(n);
+        }
+        product
+    }
+}
+
+impl<I: Iterator> CheckedIter for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_no_overflow() {
+        assert_eq!(3u64.add_ck(4).mul_ck(5).sub_ck(1), 34);
+        assert_eq!(1u64.shl_ck(63), 1 << 63);
+        assert_eq!([1u8, 2, 3].into_iter().sum_ck(), 6);
+        assert_eq!([2i64, -3, 4].into_iter().product_ck(), -24);
+        assert_eq!(300u64.cast_ck::<u16>(), 300);
+    }
+
+    #[test]
+    pub fn test_context() {
+        let _outer = context("aoc_2023::day11", 2);
+        {
+            let _inner = context("aoc_2023::day05", 1);
+            assert_eq!(CONTEXT.get(), ("day05", 1));
+        }
+        assert_eq!(CONTEXT.get(), ("day11", 2));
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "arithmetic overflow in day11 part 2: 999999 * 18446744073709551615")]
+    pub fn test_mul_overflow() {
+        let _ctx = context("aoc_2023::day11", 2);
+        999999u64.mul_ck(u64::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "arithmetic overflow in day04 part 1: 3 << 63")]
+    pub fn test_shl_overflow() {
+        let _ctx = context("aoc_2023::day04", 1);
+        3u64.shl_ck(63);
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "arithmetic overflow in day02 part 2: 255 + 1")]
+    pub fn test_sum_overflow() {
+        let _ctx = context("aoc_2023::day02", 2);
+        [255u8, 1].into_iter().sum_ck();
+    }
+}
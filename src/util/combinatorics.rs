@@ -0,0 +1,274 @@
+//! Iterators over pairs, combinations, permutations and products, and binomial coefficients
+
+use num::integer::gcd;
+use num::{BigUint, One};
+
+/// Iterate over all unordered pairs of distinct elements, i.e. `(items[i], items[j])` for `i < j`.
+pub fn pairs<T>(items: &[T]) -> impl Iterator<Item = (&T, &T)> + '_ {
+    items
+        .iter()
+        .enumerate()
+        .flat_map(move |(i, a)| items[i + 1..].iter().map(move |b| (a, b)))
+}
+
+/// Iterate over all ordered pairs of one element from `a` and one from `b`.
+pub fn product<'a, A, B>(a: &'a [A], b: &'a [B]) -> impl Iterator<Item = (&'a A, &'a B)> + 'a {
+    a.iter().flat_map(move |x| b.iter().map(move |y| (x, y)))
+}
+
+/// Iterate over all `k`-element subsets of `items`, in lexicographic order of their indices.
+pub fn combinations<T>(items: &[T], k: usize) -> Combinations<'_, T> {
+    Combinations {
+        items,
+        indices: (0..k).collect(),
+        done: k > items.len(),
+    }
+}
+
+/// Iterate over all orderings of `items`, in lexicographic order of their indices.
+pub fn permutations<T>(items: &[T]) -> Permutations<'_, T> {
+    Permutations {
+        items,
+        indices: (0..items.len()).collect(),
+        done: false,
+    }
+}
+
+/// Iterate over every way to pick one element from each slice, like nested for loops where the
+/// last slice is the innermost loop.
+pub fn cartesian<'a, T>(slices: &[&'a [T]]) -> Cartesian<'a, T> {
+    Cartesian {
+        slices: slices.to_vec(),
+        indices: vec![0; slices.len()],
+        done: slices.iter().any(|s| s.is_empty()),
+    }
+}
+
+/// Iterator returned by [combinations]
+#[derive(Debug, Clone)]
+pub struct Combinations<'a, T> {
+    items: &'a [T],
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<'a, T> Iterator for Combinations<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let out = self.indices.iter().map(|&i| &self.items[i]).collect();
+
+        // find the rightmost index that can still move right, move it, and reset the ones after
+        let (n, k) = (self.items.len(), self.indices.len());
+        match (0..k).rev().find(|&i| self.indices[i] < n - k + i) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+
+        Some(out)
+    }
+}
+
+/// Iterator returned by [permutations]
+#[derive(Debug, Clone)]
+pub struct Permutations<'a, T> {
+    items: &'a [T],
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<'a, T> Iterator for Permutations<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let out = self.indices.iter().map(|&i| &self.items[i]).collect();
+
+        // step to the next permutation in lexicographic order
+        let indices = &mut self.indices;
+        match (1..indices.len())
+            .rev()
+            .find(|&i| indices[i - 1] < indices[i])
+        {
+            Some(i) => {
+                let pivot = i - 1;
+                let swap = (i..indices.len())
+                    .rev()
+                    .find(|&j| indices[j] > indices[pivot])
+                    .expect("indices[i] is larger than the pivot");
+                indices.swap(pivot, swap);
+                indices[i..].reverse();
+            }
+            None => self.done = true,
+        }
+
+        Some(out)
+    }
+}
+
+/// Iterator returned by [cartesian]
+#[derive(Debug, Clone)]
+pub struct Cartesian<'a, T> {
+    slices: Vec<&'a [T]>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<'a, T> Iterator for Cartesian<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let out = self
+            .slices
+            .iter()
+            .zip(&self.indices)
+            .map(|(slice, &i)| &slice[i])
+            .collect();
+
+        // count up like an odometer, with the last slice as the lowest digit
+        self.done = true;
+        for (slice, i) in self.slices.iter().zip(&mut self.indices).rev() {
+            *i += 1;
+            if *i < slice.len() {
+                self.done = false;
+                break;
+            }
+            *i = 0;
+        }
+
+        Some(out)
+    }
+}
+
+/// Calculate the binomial coefficient "n choose k". Returns `None` on overflow.
+pub fn binomial(n: u64, k: u64) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+
+    // after step i, result = C(n, i + 1)
+    let mut result: u128 = 1;
+    for i in 0..u128::from(k) {
+        // divide out common factors first, so that we only overflow if the result would
+        let numerator = u128::from(n) - i;
+        let divisor = i + 1;
+        let g = gcd(result, divisor);
+        result = (result / g).checked_mul(numerator / (divisor / g))?;
+    }
+
+    Some(result)
+}
+
+/// Calculate the binomial coefficient "n choose k", without overflow.
+pub fn binomial_big(n: u64, k: u64) -> BigUint {
+    if k > n {
+        return BigUint::default();
+    }
+    let k = k.min(n - k);
+
+    let mut result = BigUint::one();
+    for i in 0..k {
+        result *= n - i;
+        result /= i + 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pairs() {
+        let pairs: Vec<_> = pairs(&[1, 2, 3]).collect();
+        assert_eq!(pairs, [(&1, &2), (&1, &3), (&2, &3)]);
+        assert_eq!(super::pairs(&[1]).count(), 0);
+
+        let product: Vec<_> = product(&[1, 2], &['a', 'b']).collect();
+        assert_eq!(product, [(&1, &'a'), (&1, &'b'), (&2, &'a'), (&2, &'b')]);
+    }
+
+    #[test]
+    pub fn test_combinations() {
+        let combinations: Vec<Vec<_>> = combinations(&['a', 'b', 'c', 'd'], 2).collect();
+        assert_eq!(
+            combinations,
+            [
+                [&'a', &'b'],
+                [&'a', &'c'],
+                [&'a', &'d'],
+                [&'b', &'c'],
+                [&'b', &'d'],
+                [&'c', &'d']
+            ]
+        );
+
+        let items: Vec<u32> = (0..10).collect();
+        for k in 0..=11 {
+            let count = super::combinations(&items, k).count() as u128;
+            assert_eq!(Some(count), binomial(10, k as u64));
+        }
+    }
+
+    #[test]
+    pub fn test_permutations() {
+        let permutations: Vec<Vec<_>> = permutations(&[1, 2, 3]).collect();
+        assert_eq!(
+            permutations,
+            [
+                [&1, &2, &3],
+                [&1, &3, &2],
+                [&2, &1, &3],
+                [&2, &3, &1],
+                [&3, &1, &2],
+                [&3, &2, &1]
+            ]
+        );
+
+        assert_eq!(super::permutations(&[0; 6]).count(), 720);
+        assert_eq!(super::permutations::<u8>(&[]).count(), 1);
+    }
+
+    #[test]
+    pub fn test_cartesian() {
+        let product: Vec<Vec<_>> = cartesian(&[&[1, 2][..], &[3], &[4, 5]]).collect();
+        assert_eq!(
+            product,
+            [[&1, &3, &4], [&1, &3, &5], [&2, &3, &4], [&2, &3, &5]]
+        );
+
+        assert_eq!(cartesian::<u8>(&[]).count(), 1);
+        assert_eq!(cartesian(&[&[1][..], &[]]).count(), 0);
+    }
+
+    #[test]
+    pub fn test_binomial() {
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(5, 6), Some(0));
+        assert_eq!(binomial(0, 0), Some(1));
+        assert_eq!(binomial(64, 32), Some(1832624140942590534));
+
+        // C(130, 65) is just below 2^128, and would overflow without the gcd trick
+        let big = binomial_big(130, 65);
+        assert_eq!(binomial(130, 65).map(BigUint::from), Some(big));
+        assert_eq!(binomial(132, 66), None);
+        assert_eq!(
+            binomial_big(132, 66).to_string(),
+            "377389666165540953244592352291892721700"
+        );
+    }
+}
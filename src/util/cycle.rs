@@ -0,0 +1,132 @@
+//! Run long simulations by detecting when the state starts repeating
+
+use super::HashMap;
+use hashers::fx_hash::FxHasher;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+
+/// A cycle detected by [simulate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first iteration of the cycle. The state after `start` steps is the same as the state
+    /// after `start + length` steps.
+    pub start: usize,
+
+    /// Number of steps in the cycle.
+    pub length: usize,
+}
+
+/// Result of [simulate]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation<S> {
+    /// The state after all iterations.
+    pub state: S,
+
+    /// The cycle that was used to skip ahead, if one was found.
+    pub cycle: Option<Cycle>,
+}
+
+/// Apply `step` to `state` `iterations` times.
+///
+/// Every state is hashed and remembered, and as soon as a state repeats we know that the rest of
+/// the simulation will loop, so we can skip straight to the result.
+pub fn simulate<S>(mut state: S, iterations: usize, mut step: impl FnMut(&mut S)) -> Simulation<S>
+where
+    S: Hash + Eq + Clone,
+{
+    let hasher = BuildHasherDefault::<FxHasher>::default();
+
+    // map from state hashes to the iterations that produced them
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::default();
+    seen.insert(hasher.hash_one(&state), vec![0]);
+
+    // all the states we've seen so far, indexed by iteration
+    let mut history = vec![state.clone()];
+
+    for i in 1..=iterations {
+        step(&mut state);
+
+        let hash = hasher.hash_one(&state);
+        let iterations_with_hash = seen.entry(hash).or_default();
+        let start = iterations_with_hash
+            .iter()
+            .copied()
+            .find(|&j| history[j] == state);
+
+        if let Some(start) = start {
+            let length = i - start;
+            let target = start + (iterations - start) % length;
+            return Simulation {
+                state: history.swap_remove(target),
+                cycle: Some(Cycle { start, length }),
+            };
+        }
+
+        iterations_with_hash.push(i);
+        history.push(state.clone());
+    }
+
+    Simulation { state, cycle: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::grid::Grid;
+
+    #[test]
+    pub fn test_numbers() {
+        // 6 -> 11 -> 5 -> 0 -> 1 -> 2 -> 5 -> ...
+        let step = |n: &mut u64| *n = (*n * *n + 1) % 13;
+
+        let sim = simulate(6, 1_000_000_000, step);
+        assert_eq!(
+            sim.cycle,
+            Some(Cycle {
+                start: 2,
+                length: 4
+            })
+        );
+
+        // (1_000_000_000 - 2) % 4 == 2, so we end up 2 steps into the cycle
+        assert_eq!(sim.state, 1);
+
+        let sim = simulate(6, 3, step);
+        assert_eq!(
+            sim,
+            Simulation {
+                state: 0,
+                cycle: None
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_grid() {
+        // shift every row one step to the right, wrapping around
+        let step = |grid: &mut Grid<u8>| {
+            let shifted: Vec<u8> = grid
+                .rows()
+                .flat_map(|row| {
+                    let (init, last) = row.split_at(row.len() - 1);
+                    last.iter().chain(init).copied()
+                })
+                .collect();
+            *grid = Grid::new(grid.width(), grid.height(), shifted);
+        };
+
+        let grid = Grid::parse("#..\n.#.\n", |b| b);
+        let sim = simulate(grid.clone(), 1_000_000_000, step);
+        assert_eq!(
+            sim.cycle,
+            Some(Cycle {
+                start: 0,
+                length: 3
+            })
+        );
+
+        // 1_000_000_000 % 3 == 1
+        let mut expected = grid;
+        step(&mut expected);
+        assert_eq!(sim.state, expected);
+    }
+}
@@ -0,0 +1,196 @@
+//! Lattice polygon helpers, e.g. for counting the tiles enclosed by a loop
+//!
+//! Polygons are given as a closed path of vertices, where the last vertex connects back to the
+//! first. Repeating the first vertex at the end is fine. All arithmetic is done in i128, so areas
+//! far beyond what fits in an i64 are fine. Polygons with coordinates close to the i64 limits can
+//! have areas that don't fit in an i128 though, so the area functions return `None` for those.
+
+use num::integer::gcd;
+use num::BigInt;
+use std::cmp::Ordering;
+
+/// An (x, y) lattice point
+pub type Point = (i64, i64);
+
+/// Where a point is in relation to a polygon. See [point_in_polygon].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Iterate over the edges of a closed path, as pairs of (i128) points
+fn edges(path: &[Point]) -> impl Iterator<Item = [(i128, i128); 2]> + '_ {
+    let wide = |&(x, y): &Point| (i128::from(x), i128::from(y));
+    let next = path.iter().cycle().skip(1);
+    path.iter().zip(next).map(move |(a, b)| [wide(a), wide(b)])
+}
+
+/// Calculate twice the signed area of a polygon using the shoelace formula.
+///
+/// The area is positive if the vertices go counter-clockwise (with y pointing up). It's doubled so
+/// that it's always an integer. Returns `None` if it doesn't fit in an i128.
+pub fn signed_double_area(path: &[Point]) -> Option<i128> {
+    edges(path).try_fold(0i128, |area, [(x1, y1), (x2, y2)]| {
+        let term = x1.checked_mul(y2)?.checked_sub(x2.checked_mul(y1)?)?;
+        area.checked_add(term)
+    })
+}
+
+/// Count the lattice points on the boundary of a polygon.
+///
+/// This is the number of unit steps along the closed path, which is the number of boundary points
+/// for polygons with at least 3 vertices that don't touch themselves. Degenerate paths count every
+/// time the path passes a point: a single point gives 0, and the segment `[(0, 0), (4, 0)]` gives
+/// 8, since the path goes there and back.
+///
+/// Each edge has at most 2^64 points, so this can't overflow.
+pub fn boundary_points(path: &[Point]) -> i128 {
+    edges(path)
+        .map(|[(x1, y1), (x2, y2)]| gcd(x2 - x1, y2 - y1))
+        .sum()
+}
+
+/// Count the lattice points strictly inside a polygon, using Pick's theorem.
+///
+/// The polygon must not intersect itself. Degenerate polygons with no area, like empty paths,
+/// single points and lines, have no interior points. Returns `None` if the area doesn't fit in an
+/// i128.
+pub fn interior_points(path: &[Point]) -> Option<i128> {
+    let double_area = signed_double_area(path)?.abs();
+    if double_area == 0 {
+        return Some(0);
+    }
+
+    // Pick's theorem: A = i + b/2 - 1. The boundary is never more than the doubled area, so this
+    // can't overflow.
+    let boundary = boundary_points(path);
+    Some((double_area - boundary + 2) / 2)
+}
+
+/// Compare `a * b` with `c * d` exactly, even if the products don't fit in an i128.
+fn cmp_products(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    match (a.checked_mul(b), c.checked_mul(d)) {
+        (Some(ab), Some(cd)) => ab.cmp(&cd),
+        _ => (BigInt::from(a) * b).cmp(&(BigInt::from(c) * d)),
+    }
+}
+
+/// Check whether a point is inside, outside, or on the boundary of a polygon.
+pub fn point_in_polygon(path: &[Point], point: Point) -> Containment {
+    let (px, py) = (i128::from(point.0), i128::from(point.1));
+    let mut inside = false;
+
+    for [(x1, y1), (x2, y2)] in edges(path) {
+        let on_line = cmp_products(x2 - x1, py - y1, px - x1, y2 - y1).is_eq();
+        let in_box =
+            (x1.min(x2)..=x1.max(x2)).contains(&px) && (y1.min(y2)..=y1.max(y2)).contains(&py);
+        if on_line && in_box {
+            return Containment::Boundary;
+        }
+
+        // cast a ray in the +x direction, and count the edges it crosses
+        if (y1 > py) != (y2 > py) {
+            let dy = y2 - y1;
+            let side = cmp_products(px - x1, dy, py - y1, x2 - x1);
+            if (dy > 0 && side.is_lt()) || (dy < 0 && side.is_gt()) {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Containment::Inside
+    } else {
+        Containment::Outside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_square() {
+        let square = [(0, 0), (4, 0), (4, 4), (0, 4)];
+        assert_eq!(signed_double_area(&square), Some(32));
+        assert_eq!(boundary_points(&square), 16);
+        assert_eq!(interior_points(&square), Some(9));
+
+        let clockwise: Vec<_> = square.iter().rev().copied().collect();
+        assert_eq!(signed_double_area(&clockwise), Some(-32));
+        assert_eq!(interior_points(&clockwise), Some(9));
+
+        let mut closed = square.to_vec();
+        closed.push((0, 0));
+        assert_eq!(interior_points(&closed), Some(9));
+    }
+
+    #[test]
+    pub fn test_triangle() {
+        let triangle = [(0, 0), (3, 1), (1, 3)];
+        assert_eq!(signed_double_area(&triangle), Some(8));
+        assert_eq!(boundary_points(&triangle), 4);
+        assert_eq!(interior_points(&triangle), Some(3));
+
+        let huge = [(0, 0), (i64::MAX, 0), (i64::MAX, i64::MAX)];
+        let side = i128::from(i64::MAX);
+        assert_eq!(signed_double_area(&huge), Some(side * side));
+    }
+
+    #[test]
+    pub fn test_degenerate() {
+        assert_eq!(signed_double_area(&[]), Some(0));
+        assert_eq!(interior_points(&[]), Some(0));
+        assert_eq!(boundary_points(&[]), 0);
+        assert_eq!(interior_points(&[(3, 5)]), Some(0));
+        assert_eq!(boundary_points(&[(3, 5)]), 0);
+        assert_eq!(interior_points(&[(0, 0), (4, 0)]), Some(0));
+        assert_eq!(boundary_points(&[(0, 0), (4, 0)]), 8);
+    }
+
+    #[test]
+    pub fn test_overflow() {
+        // twice the area is about 2^128, which doesn't fit
+        let corners = [
+            (i64::MIN, i64::MIN),
+            (i64::MAX, i64::MIN),
+            (i64::MIN, i64::MAX),
+        ];
+        assert_eq!(signed_double_area(&corners), None);
+        assert_eq!(interior_points(&corners), None);
+        assert_eq!(boundary_points(&corners), 3 * (1 << 64) - 3);
+
+        // the cross products in here don't fit in an i128 either
+        let square = [
+            (i64::MIN, i64::MIN),
+            (i64::MAX, i64::MIN),
+            (i64::MAX, i64::MAX),
+            (i64::MIN, i64::MAX),
+        ];
+        assert_eq!(point_in_polygon(&square, (0, 0)), Containment::Inside);
+        assert_eq!(
+            point_in_polygon(&square, (i64::MAX, 7)),
+            Containment::Boundary
+        );
+        assert_eq!(
+            point_in_polygon(&corners, (i64::MAX, i64::MAX)),
+            Containment::Outside
+        );
+        assert_eq!(point_in_polygon(&corners, (0, -1)), Containment::Boundary);
+    }
+
+    #[test]
+    pub fn test_point_in_polygon() {
+        // an L-shape
+        let l = [(0, 0), (4, 0), (4, 2), (2, 2), (2, 4), (0, 4)];
+        assert_eq!(point_in_polygon(&l, (1, 1)), Containment::Inside);
+        assert_eq!(point_in_polygon(&l, (1, 3)), Containment::Inside);
+        assert_eq!(point_in_polygon(&l, (3, 3)), Containment::Outside);
+        assert_eq!(point_in_polygon(&l, (2, 3)), Containment::Boundary);
+        assert_eq!(point_in_polygon(&l, (4, 0)), Containment::Boundary);
+        assert_eq!(point_in_polygon(&l, (-1, 2)), Containment::Outside);
+        assert_eq!(point_in_polygon(&l, (1, 2)), Containment::Inside);
+    }
+}
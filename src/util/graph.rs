@@ -0,0 +1,468 @@
+//! Flow and cut algorithms on undirected, weighted graphs
+//!
+//! Nodes are dense `u32` IDs, so a graph can be built straight from an [Interner]:
+//!
+//! ```ignore
+//! let mut names = Interner::new();
+//! let mut graph = Graph::new();
+//! for (a, b) in wires {
+//!     graph.add_edge(names.intern(a), names.intern(b), 1);
+//! }
+//! let cut = graph.min_cut().unwrap();
+//! assert_eq!(cut.edges.len(), 3);
+//! ```
+//!
+//! [Interner]: super::intern::Interner
+
+use super::union_find::UnionFind;
+use super::HashMap;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// An undirected graph with weighted edges, on the nodes `0..len`
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: usize,
+    edges: Vec<(u32, u32, u64)>,
+}
+
+/// A partition of the nodes of a [Graph] into two sets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// Total weight of the edges crossing the cut.
+    pub weight: u64,
+
+    /// The edges crossing the cut, as `(a, b)` where `a` is in the first set.
+    pub edges: Vec<(u32, u32)>,
+
+    /// Whether each node is in the first set.
+    pub side: Vec<bool>,
+
+    /// Number of nodes in the first and second set.
+    pub sizes: [usize; 2],
+}
+
+/// Result of [Graph::max_flow]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flow {
+    /// The maximum flow from source to sink.
+    pub value: u64,
+
+    /// A minimum cut separating source and sink, with the source in the first set.
+    pub cut: Cut,
+}
+
+/// A small, seedable pseudo-random number generator (SplitMix64)
+///
+/// Not suitable for anything but making randomized algorithms reproducible.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed float in `(0, 1]`.
+    pub fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a graph with `nodes` nodes and no edges.
+    pub fn with_nodes(nodes: usize) -> Self {
+        Graph {
+            nodes,
+            edges: vec![],
+        }
+    }
+
+    /// Add an undirected edge. The graph grows to include both nodes if needed.
+    ///
+    /// Parallel edges are allowed, and behave like a single edge with the sum of their weights.
+    pub fn add_edge(&mut self, a: u32, b: u32, weight: u64) {
+        self.nodes = self.nodes.max(a.max(b) as usize + 1);
+        self.edges.push((a, b, weight));
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    /// All edges as `(a, b, weight)`, in the order they were added.
+    pub fn edges(&self) -> &[(u32, u32, u64)] {
+        &self.edges
+    }
+
+    /// Create a [Cut] from which side each node is on.
+    pub fn cut(&self, side: Vec<bool>) -> Cut {
+        assert_eq!(side.len(), self.nodes, "every node must have a side");
+
+        let mut weight = 0;
+        let mut edges = vec![];
+        for &(a, b, w) in &self.edges {
+            match (side[a as usize], side[b as usize]) {
+                (true, false) => edges.push((a, b)),
+                (false, true) => edges.push((b, a)),
+                _ => continue,
+            }
+            weight += w;
+        }
+
+        let first = side.iter().filter(|&&s| s).count();
+        Cut {
+            weight,
+            edges,
+            sizes: [first, side.len() - first],
+            side,
+        }
+    }
+
+    /// Find the maximum flow from `source` to `sink` using Dinic's algorithm, treating edge
+    /// weights as capacities in both directions.
+    pub fn max_flow(&self, source: u32, sink: u32) -> Flow {
+        assert_ne!(source, sink, "source and sink must be different");
+        let (source, sink) = (source as usize, sink as usize);
+
+        // edges are stored in pairs, so that the reverse of edge `e` is `e ^ 1`
+        let mut to = Vec::with_capacity(self.edges.len() * 2);
+        let mut capacity = Vec::with_capacity(self.edges.len() * 2);
+        let mut outgoing = vec![vec![]; self.nodes];
+        for &(a, b, w) in &self.edges {
+            outgoing[a as usize].push(to.len());
+            to.push(b as usize);
+            capacity.push(w);
+            outgoing[b as usize].push(to.len());
+            to.push(a as usize);
+            capacity.push(w);
+        }
+
+        // distance from the source in the residual graph
+        let levels = |capacity: &[u64]| {
+            let mut level = vec![usize::MAX; self.nodes];
+            level[source] = 0;
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                for &e in &outgoing[node] {
+                    if capacity[e] > 0 && level[to[e]] == usize::MAX {
+                        level[to[e]] = level[node] + 1;
+                        queue.push_back(to[e]);
+                    }
+                }
+            }
+            level
+        };
+
+        struct Dfs<'a> {
+            to: &'a [usize],
+            outgoing: &'a [Vec<usize>],
+            level: &'a [usize],
+            next_edge: Vec<usize>,
+            sink: usize,
+        }
+
+        impl Dfs<'_> {
+            /// Push up to `limit` flow from `node` to the sink along the level graph.
+            fn push(&mut self, capacity: &mut [u64], node: usize, limit: u64) -> u64 {
+                if node == self.sink {
+                    return limit;
+                }
+
+                while let Some(&e) = self.outgoing[node].get(self.next_edge[node]) {
+                    let next = self.to[e];
+                    if capacity[e] > 0 && self.level[next] == self.level[node] + 1 {
+                        let pushed = self.push(capacity, next, limit.min(capacity[e]));
+                        if pushed > 0 {
+                            capacity[e] -= pushed;
+                            capacity[e ^ 1] += pushed;
+                            return pushed;
+                        }
+                    }
+                    self.next_edge[node] += 1;
+                }
+
+                0
+            }
+        }
+
+        let mut value: u64 = 0;
+        loop {
+            let level = levels(&capacity);
+            if level[sink] == usize::MAX {
+                let side = level.iter().map(|&l| l != usize::MAX).collect();
+                return Flow {
+                    value,
+                    cut: self.cut(side),
+                };
+            }
+
+            let mut dfs = Dfs {
+                to: &to,
+                outgoing: &outgoing,
+                level: &level,
+                next_edge: vec![0; self.nodes],
+                sink,
+            };
+            loop {
+                let pushed = dfs.push(&mut capacity, source, u64::MAX);
+                if pushed == 0 {
+                    break;
+                }
+                value = value.checked_add(pushed).expect("flow overflowed");
+            }
+        }
+    }
+
+    /// Find a global minimum cut using the Stoer–Wagner algorithm.
+    ///
+    /// Returns `None` if there are fewer than two nodes.
+    pub fn min_cut(&self) -> Option<Cut> {
+        if self.nodes < 2 {
+            return None;
+        }
+
+        // adjacency of the merged nodes, with parallel edges summed up
+        let mut adjacent: Vec<HashMap<u32, u64>> = vec![HashMap::default(); self.nodes];
+        for &(a, b, w) in &self.edges {
+            if a != b {
+                *adjacent[a as usize].entry(b).or_default() += w;
+                *adjacent[b as usize].entry(a).or_default() += w;
+            }
+        }
+
+        // the original nodes that have been merged into each node
+        let mut members: Vec<Vec<u32>> = (0..self.nodes as u32).map(|n| vec![n]).collect();
+        let mut active: Vec<u32> = (0..self.nodes as u32).collect();
+        let mut best: Option<(u64, Vec<u32>)> = None;
+
+        while active.len() > 1 {
+            // order the nodes by how strongly they're connected to the nodes before them
+            let mut connectivity = vec![0u64; self.nodes];
+            let mut added = vec![false; self.nodes];
+            let mut order = Vec::with_capacity(active.len());
+            let mut queue = BinaryHeap::from([(0, active[0])]);
+            while let Some((weight, node)) = queue.pop() {
+                if added[node as usize] || weight != connectivity[node as usize] {
+                    continue;
+                }
+                added[node as usize] = true;
+                order.push(node);
+
+                for (&next, &w) in &adjacent[node as usize] {
+                    if !added[next as usize] {
+                        connectivity[next as usize] += w;
+                        queue.push((connectivity[next as usize], next));
+                    }
+                }
+            }
+
+            if order.len() < active.len() {
+                // the graph isn't connected, so there's a cut with no edges at all
+                let mut side = vec![false; self.nodes];
+                for &node in &order {
+                    for &member in &members[node as usize] {
+                        side[member as usize] = true;
+                    }
+                }
+                return Some(self.cut(side));
+            }
+
+            // the cut between the last node and everything else is a minimum cut between the
+            // last two nodes, so now we can merge them
+            let [.., s, t] = order[..] else {
+                unreachable!("there are at least two active nodes");
+            };
+            let cut_weight = connectivity[t as usize];
+            if !best.as_ref().is_some_and(|&(w, _)| w <= cut_weight) {
+                best = Some((cut_weight, members[t as usize].clone()));
+            }
+
+            let merged = std::mem::take(&mut members[t as usize]);
+            members[s as usize].extend(merged);
+            for (next, w) in std::mem::take(&mut adjacent[t as usize]) {
+                adjacent[next as usize].remove(&t);
+                if next != s {
+                    *adjacent[s as usize].entry(next).or_default() += w;
+                    *adjacent[next as usize].entry(s).or_default() += w;
+                }
+            }
+            active.retain(|&node| node != t);
+        }
+
+        let (_, first) = best?;
+        let mut side = vec![false; self.nodes];
+        for node in first {
+            side[node as usize] = true;
+        }
+        Some(self.cut(side))
+    }
+
+    /// Find a random cut using one run of Karger's contraction algorithm.
+    ///
+    /// Edges are contracted in a random order, where heavier edges are more likely to go first,
+    /// until only two groups of nodes are left. The result is a minimum cut with probability at
+    /// least `2 / n²`, so it usually needs to be repeated, see [Graph::karger_min_cut].
+    ///
+    /// Returns `None` if there are fewer than two nodes.
+    pub fn karger(&self, rng: &mut Rng) -> Option<Cut> {
+        if self.nodes < 2 {
+            return None;
+        }
+
+        // sorting by exponentially distributed keys is the same as repeatedly picking a random
+        // edge with probability proportional to its weight
+        let mut order: Vec<(f64, u32, u32)> = self
+            .edges
+            .iter()
+            .filter(|&&(_, _, w)| w > 0)
+            .map(|&(a, b, w)| (-rng.next_f64().ln() / w as f64, a, b))
+            .collect();
+        order.sort_unstable_by(|x, y| x.0.total_cmp(&y.0));
+
+        let mut groups = UnionFind::new(self.nodes);
+        for (_, a, b) in order {
+            if groups.component_count() == 2 {
+                break;
+            }
+            groups.union(a as usize, b as usize);
+        }
+
+        // if the graph isn't connected there may be more groups left, so put everything that
+        // isn't with node 0 on the other side
+        let first = groups.find(0);
+        let side = (0..self.nodes).map(|n| groups.find(n) == first).collect();
+        Some(self.cut(side))
+    }
+
+    /// Run [Graph::karger] `trials` times, and return the smallest cut found.
+    pub fn karger_min_cut(&self, rng: &mut Rng, trials: usize) -> Option<Cut> {
+        (0..trials)
+            .filter_map(|_| self.karger(rng))
+            .min_by_key(|cut| cut.weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::intern::Interner;
+
+    const WIRES: &str = "\
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr
+";
+
+    fn wires() -> (Interner<'static>, Graph) {
+        let mut names = Interner::new();
+        let mut graph = Graph::new();
+        for line in WIRES.lines() {
+            let (from, to) = line.split_once(": ").unwrap();
+            for to in to.split(' ') {
+                graph.add_edge(names.intern(from), names.intern(to), 1);
+            }
+        }
+        (names, graph)
+    }
+
+    /// Check that `cut` is the three wire cut from the example.
+    fn check_wires(names: &Interner, cut: &Cut) {
+        assert_eq!(cut.weight, 3);
+
+        let mut sizes = cut.sizes;
+        sizes.sort();
+        assert_eq!(sizes, [6, 9]);
+
+        let mut edges: Vec<_> = cut
+            .edges
+            .iter()
+            .map(|&(a, b)| {
+                let mut edge = [names.name(a), names.name(b)];
+                edge.sort();
+                edge
+            })
+            .collect();
+        edges.sort();
+        assert_eq!(edges, [["bvb", "cmg"], ["hfx", "pzl"], ["jqt", "nvd"]]);
+    }
+
+    #[test]
+    pub fn test_max_flow() {
+        let (names, graph) = wires();
+        let source = names.get("jqt").unwrap();
+        let sink = names.get("cmg").unwrap();
+
+        let flow = graph.max_flow(source, sink);
+        assert_eq!(flow.value, 3);
+        assert!(flow.cut.side[source as usize]);
+        assert!(!flow.cut.side[sink as usize]);
+        check_wires(&names, &flow.cut);
+
+        let mut graph = Graph::new();
+        graph.add_edge(0, 1, 3);
+        graph.add_edge(1, 3, 2);
+        graph.add_edge(0, 2, 4);
+        graph.add_edge(2, 3, 5);
+        graph.add_edge(1, 2, 10);
+        let flow = graph.max_flow(0, 3);
+        assert_eq!(flow.value, 7);
+        assert_eq!(flow.cut.sizes, [1, 3]);
+    }
+
+    #[test]
+    pub fn test_min_cut() {
+        let (names, graph) = wires();
+        check_wires(&names, &graph.min_cut().unwrap());
+
+        let mut disconnected = Graph::with_nodes(3);
+        disconnected.add_edge(0, 1, 5);
+        let cut = disconnected.min_cut().unwrap();
+        assert_eq!(cut.weight, 0);
+        assert_eq!(cut.sizes, [2, 1]);
+
+        assert_eq!(Graph::with_nodes(1).min_cut(), None);
+    }
+
+    #[test]
+    pub fn test_karger() {
+        let (names, graph) = wires();
+        let mut rng = Rng::new(2023);
+        check_wires(&names, &graph.karger_min_cut(&mut rng, 100).unwrap());
+
+        // the same seed gives the same cuts
+        let cuts = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..5).map(|_| graph.karger(&mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(cuts(7), cuts(7));
+    }
+}
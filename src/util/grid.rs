@@ -0,0 +1,231 @@
+//! A dense 2D grid, for puzzles where the input is a map of characters
+//!
+//! See also [super::sparse_grid] for grids where only a few cells are interesting.
+
+use super::{get_many_mut, GetManyMutError};
+use std::ops::{Index, IndexMut};
+
+/// (x, y) coordinate of a cell in a [Grid]
+pub type Pos = (usize, usize);
+
+/// (x, y) coordinate that may be outside of a [Grid], e.g. for a [Tiled] view
+pub type SignedPos = (i64, i64);
+
+/// A dense, row-major 2D grid
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid from its cells, in row-major order.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            width * height,
+            cells.len(),
+            "grid has the wrong number of cells"
+        );
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parse a grid from lines of text, converting each byte with `f`.
+    ///
+    /// All lines must have the same length.
+    pub fn parse(input: &str, mut f: impl FnMut(u8) -> T) -> Self {
+        let mut width = None;
+        let mut cells = vec![];
+        for line in input.lines() {
+            let line_width = *width.get_or_insert(line.len());
+            assert_eq!(
+                line_width,
+                line.len(),
+                "all lines must have the same length"
+            );
+            cells.extend(line.bytes().map(&mut f));
+        }
+
+        let width = width.unwrap_or(0);
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        Grid::new(width, height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Convert a position to an index into the cell vec. Returns `None` if out of bounds.
+    fn index_of(&self, (x, y): Pos) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.cells[i])
+    }
+
+    /// Return mutable references to `N` different cells in the grid.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        positions: [Pos; N],
+    ) -> Result<[&mut T; N], GetManyMutError> {
+        let mut indices = [0; N];
+        for (i, (index, pos)) in indices.iter_mut().zip(positions).enumerate() {
+            *index = self.index_of(pos).ok_or(GetManyMutError::OutOfBounds(i))?;
+        }
+        get_many_mut(&mut self.cells, indices)
+    }
+
+    /// Iterate over the rows of the grid.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        // chunks panics on a chunk size of 0
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Iterate over all cells of the grid, along with their positions.
+    pub fn cells(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    /// Get an infinite view of the grid, repeated in every direction.
+    pub fn tiled(&self) -> Tiled<'_, T> {
+        assert!(!self.cells.is_empty(), "can't tile an empty grid");
+        Tiled { grid: self }
+    }
+}
+
+/// An infinite view of a [Grid], repeated in every direction
+///
+/// Position `(0, 0)` is the top left cell of the original grid, and `(-1, -1)` is the bottom
+/// right cell of the copy above and to the left of it.
+#[derive(Debug)]
+pub struct Tiled<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+// derive would require T: Copy
+impl<T> Clone for Tiled<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Tiled<'_, T> {}
+
+impl<'a, T> Tiled<'a, T> {
+    fn size(&self) -> SignedPos {
+        let signed = |n: usize| i64::try_from(n).expect("grid is too large to tile");
+        (signed(self.grid.width), signed(self.grid.height))
+    }
+
+    /// Map a position to the corresponding position in the original grid.
+    pub fn wrap(&self, (x, y): SignedPos) -> Pos {
+        let (w, h) = self.size();
+        (x.rem_euclid(w) as usize, y.rem_euclid(h) as usize)
+    }
+
+    /// Get which copy of the grid a position is in. The original grid is tile `(0, 0)`.
+    pub fn tile(&self, (x, y): SignedPos) -> SignedPos {
+        let (w, h) = self.size();
+        (x.div_euclid(w), y.div_euclid(h))
+    }
+
+    pub fn get(&self, pos: SignedPos) -> &'a T {
+        &self.grid[self.wrap(pos)]
+    }
+
+    /// The underlying grid.
+    pub fn grid(&self) -> &'a Grid<T> {
+        self.grid
+    }
+}
+
+impl<T> Index<SignedPos> for Tiled<'_, T> {
+    type Output = T;
+
+    fn index(&self, pos: SignedPos) -> &T {
+        self.get(pos)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos).expect("grid position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos).expect("grid position out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_parse() {
+        let grid = Grid::parse("#..\n.#.\n", |b| b == b'#');
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert!(grid[(0, 0)]);
+        assert!(grid[(1, 1)]);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.rows().count(), 2);
+    }
+
+    #[test]
+    pub fn test_get_many_mut() {
+        let mut grid = Grid::new(2, 2, vec![1, 2, 3, 4]);
+
+        let [a, b] = grid.get_many_mut([(1, 0), (0, 1)]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(grid, Grid::new(2, 2, vec![1, 3, 2, 4]));
+
+        // (2, 0) would map to the same index as (0, 1) if we didn't check the bounds
+        assert_eq!(
+            grid.get_many_mut([(0, 1), (2, 0)]),
+            Err(GetManyMutError::OutOfBounds(1))
+        );
+        assert_eq!(
+            grid.get_many_mut([(1, 1), (0, 0), (1, 1)]),
+            Err(GetManyMutError::Duplicate(2))
+        );
+    }
+
+    #[test]
+    pub fn test_tiled() {
+        let grid = Grid::parse("ab\ncd\nef\n", |b| b);
+        let tiled = grid.tiled();
+
+        assert_eq!(tiled[(0, 0)], b'a');
+        assert_eq!(tiled[(3, 2)], b'f');
+        assert_eq!(tiled[(-1, -1)], b'f');
+        assert_eq!(tiled[(-2, -4)], b'e');
+        assert_eq!(tiled[(i64::MIN, i64::MAX)], b'c');
+
+        assert_eq!(tiled.wrap((-3, 7)), (1, 1));
+        assert_eq!(tiled.tile((-3, 7)), (-2, 2));
+        assert_eq!(tiled.tile((1, 2)), (0, 0));
+        assert_eq!(tiled.tile((-1, 0)), (-1, 0));
+    }
+}
@@ -0,0 +1,118 @@
+//! Mapping of names to dense integer IDs, e.g. for graph nodes
+
+use super::HashMap;
+
+/// Assigns each distinct name an ID in `0..len`, in the order they were first interned
+///
+/// Each ID can also have some flags of type `F` attached to it, e.g. to remember that a node
+/// is a start or goal node without having to look at its name again.
+#[derive(Debug, Clone)]
+pub struct Interner<'a, F = ()> {
+    ids: HashMap<&'a str, u32>,
+    names: Vec<&'a str>,
+    flags: Vec<F>,
+}
+
+impl<F> Default for Interner<'_, F> {
+    fn default() -> Self {
+        Interner {
+            ids: HashMap::default(),
+            names: vec![],
+            flags: vec![],
+        }
+    }
+}
+
+impl<'a, F> Interner<'a, F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the ID of `name`, or assign it a new one.
+    ///
+    /// If `name` is new, `flags` is called to create the flags for it.
+    pub fn intern_with(&mut self, name: &'a str, flags: impl FnOnce(&str) -> F) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = u32::try_from(self.names.len()).expect("too many names to intern");
+        self.ids.insert(name, id);
+        self.names.push(name);
+        self.flags.push(flags(name));
+        id
+    }
+
+    /// Get the ID of `name`, if it has been interned.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Get the name of an ID.
+    pub fn name(&self, id: u32) -> &'a str {
+        self.names[id as usize]
+    }
+
+    pub fn flags(&self, id: u32) -> &F {
+        &self.flags[id as usize]
+    }
+
+    pub fn flags_mut(&mut self, id: u32) -> &mut F {
+        &mut self.flags[id as usize]
+    }
+
+    /// Number of interned names. All IDs are less than this.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterate over all IDs, along with their names and flags.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a str, &F)> + '_ {
+        (0..)
+            .zip(&self.names)
+            .zip(&self.flags)
+            .map(|((id, &name), flags)| (id, name, flags))
+    }
+}
+
+impl<'a, F: Default> Interner<'a, F> {
+    /// Get the ID of `name`, or assign it a new one with default flags.
+    pub fn intern(&mut self, name: &'a str) -> u32 {
+        self.intern_with(name, |_| F::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_interner() {
+        let mut names: Interner<bool> = Interner::new();
+        let ends_with_z = |name: &str| name.ends_with('Z');
+
+        assert_eq!(names.intern_with("AAA", ends_with_z), 0);
+        assert_eq!(names.intern_with("ZZZ", ends_with_z), 1);
+        assert_eq!(names.intern("AAA"), 0);
+        assert_eq!(names.intern("BBB"), 2);
+
+        assert_eq!(names.len(), 3);
+        assert_eq!(names.get("ZZZ"), Some(1));
+        assert_eq!(names.get("CCC"), None);
+        assert_eq!(names.name(2), "BBB");
+        assert!(names.flags(1));
+        assert!(!names.flags(0));
+
+        *names.flags_mut(0) = true;
+        let flagged: Vec<_> = names
+            .iter()
+            .filter(|(_, _, &z)| z)
+            .map(|(_, n, _)| n)
+            .collect();
+        assert_eq!(flagged, ["AAA", "ZZZ"]);
+    }
+}
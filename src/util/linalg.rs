@@ -0,0 +1,428 @@
+//! Exact linear algebra, for puzzles where floats aren't precise enough
+
+use num::{BigInt, BigRational, One, Zero};
+use std::ops::{Index, IndexMut};
+
+/// A small dense row-major matrix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+/// Where two lines intersect, if at all. See [intersect_lines_2d] and [intersect_lines_3d].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intersection<const N: usize> {
+    /// The lines cross at `point`, which is at `p1 + t*d1` and at `p2 + s*d2`.
+    Point {
+        point: [BigRational; N],
+        t: BigRational,
+        s: BigRational,
+    },
+
+    /// The lines are the same line.
+    Coincident,
+
+    /// The lines never meet. They are either parallel, or skew.
+    None,
+}
+
+impl<T> Matrix<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map(|row| row.len()).unwrap_or(0);
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "all rows must have the same length"
+        );
+
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.cols {
+            self.data.swap(a * self.cols + col, b * self.cols + col);
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    /// Index by (row, column)
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "matrix index out of bounds"
+        );
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "matrix index out of bounds"
+        );
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl Matrix<BigRational> {
+    /// Transform the matrix into reduced row echelon form using gaussian elimination.
+    ///
+    /// Returns the rank of the matrix, and whether an odd number of row swaps were made.
+    pub fn row_reduce(&mut self) -> (usize, bool) {
+        let mut rank = 0;
+        let mut odd_swaps = false;
+
+        for col in 0..self.cols {
+            let Some(pivot) = (rank..self.rows).find(|&row| !self[(row, col)].is_zero()) else {
+                continue;
+            };
+
+            if pivot != rank {
+                self.swap_rows(pivot, rank);
+                odd_swaps = !odd_swaps;
+            }
+
+            // normalize the pivot row
+            let pivot_value = self[(rank, col)].clone();
+            for c in col..self.cols {
+                self[(rank, c)] /= &pivot_value;
+            }
+
+            // eliminate the column from all other rows
+            for row in (0..self.rows).filter(|&row| row != rank) {
+                let factor = self[(row, col)].clone();
+                if factor.is_zero() {
+                    continue;
+                }
+                for c in col..self.cols {
+                    let delta = &factor * &self[(rank, c)];
+                    self[(row, c)] -= delta;
+                }
+            }
+
+            rank += 1;
+        }
+
+        (rank, odd_swaps)
+    }
+
+    /// Calculate the determinant of a square matrix.
+    pub fn determinant(&self) -> BigRational {
+        assert_eq!(self.rows, self.cols, "matrix must be square");
+
+        // eliminate down to row echelon form, the determinant is then the product of the pivots
+        let mut m = self.clone();
+        let mut det = BigRational::one();
+        for col in 0..m.cols {
+            let Some(pivot) = (col..m.rows).find(|&row| !m[(row, col)].is_zero()) else {
+                return BigRational::zero();
+            };
+
+            if pivot != col {
+                m.swap_rows(pivot, col);
+                det = -det;
+            }
+
+            let pivot_value = m[(col, col)].clone();
+            for row in col + 1..m.rows {
+                let factor = &m[(row, col)] / &pivot_value;
+                for c in col..m.cols {
+                    let delta = &factor * &m[(col, c)];
+                    m[(row, c)] -= delta;
+                }
+            }
+
+            det *= pivot_value;
+        }
+
+        det
+    }
+
+    /// Solve `self * x = b` for x. Returns `None` unless there's exactly one solution.
+    pub fn solve(&self, b: &[BigRational]) -> Option<Vec<BigRational>> {
+        assert_eq!(self.rows, b.len(), "b must have one element per row");
+
+        let mut augmented = Matrix::from_rows(
+            (0..self.rows)
+                .map(|row| {
+                    let mut row_values = self.data[row * self.cols..][..self.cols].to_vec();
+                    row_values.push(b[row].clone());
+                    row_values
+                })
+                .collect(),
+        );
+
+        let (rank, _) = augmented.row_reduce();
+
+        // the system is inconsistent if a row reduced to 0 = c, with c != 0
+        let inconsistent = (0..augmented.rows).any(|row| {
+            (0..self.cols).all(|col| augmented[(row, col)].is_zero())
+                && !augmented[(row, self.cols)].is_zero()
+        });
+
+        if inconsistent || rank != self.cols {
+            return None;
+        }
+
+        Some(
+            (0..self.cols)
+                .map(|row| augmented[(row, self.cols)].clone())
+                .collect(),
+        )
+    }
+}
+
+impl Matrix<i128> {
+    /// Convert to a matrix of rationals, e.g. to call [Matrix::solve].
+    pub fn to_rational(&self) -> Matrix<BigRational> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|&n| rational(n)).collect(),
+        }
+    }
+
+    /// Calculate the determinant of a square matrix, using the fraction-free Bareiss algorithm.
+    ///
+    /// Returns `None` on overflow.
+    pub fn determinant(&self) -> Option<i128> {
+        assert_eq!(self.rows, self.cols, "matrix must be square");
+        let n = self.rows;
+        if n == 0 {
+            return Some(1);
+        }
+
+        let mut m = self.clone();
+        let mut negate = false;
+        let mut prev_pivot = 1i128;
+
+        for k in 0..n - 1 {
+            if m[(k, k)] == 0 {
+                let Some(pivot) = (k + 1..n).find(|&row| m[(row, k)] != 0) else {
+                    return Some(0);
+                };
+                m.swap_rows(pivot, k);
+                negate = !negate;
+            }
+
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let a = m[(i, j)].checked_mul(m[(k, k)])?;
+                    let b = m[(i, k)].checked_mul(m[(k, j)])?;
+                    // this division is always exact
+                    m[(i, j)] = a.checked_sub(b)? / prev_pivot;
+                }
+            }
+            prev_pivot = m[(k, k)];
+        }
+
+        let det = m[(n - 1, n - 1)];
+        if negate {
+            det.checked_neg()
+        } else {
+            Some(det)
+        }
+    }
+}
+
+fn rational(n: i128) -> BigRational {
+    BigRational::from(BigInt::from(n))
+}
+
+/// Find where the lines `p1 + t*d1` and `p2 + s*d2` intersect.
+pub fn intersect_lines_2d(
+    p1: [i128; 2],
+    d1: [i128; 2],
+    p2: [i128; 2],
+    d2: [i128; 2],
+) -> Intersection<2> {
+    let cross = |a: [BigInt; 2], b: [BigInt; 2]| &a[0] * &b[1] - &a[1] * &b[0];
+    let big = |v: [i128; 2]| v.map(BigInt::from);
+
+    // subtract after widening, since the difference of two i128s might not fit in one
+    let delta = [0, 1].map(|i| BigInt::from(p2[i]) - BigInt::from(p1[i]));
+    let denominator = cross(big(d1), big(d2));
+
+    if denominator.is_zero() {
+        return if cross(delta, big(d1)).is_zero() {
+            Intersection::Coincident
+        } else {
+            Intersection::None
+        };
+    }
+
+    let t = BigRational::new(cross(delta.clone(), big(d2)), denominator.clone());
+    let s = BigRational::new(cross(delta, big(d1)), denominator);
+    let point = [0, 1].map(|i| rational(p1[i]) + &t * rational(d1[i]));
+
+    Intersection::Point { point, t, s }
+}
+
+/// Find where the lines `p1 + t*d1` and `p2 + s*d2` intersect.
+pub fn intersect_lines_3d(
+    p1: [i128; 3],
+    d1: [i128; 3],
+    p2: [i128; 3],
+    d2: [i128; 3],
+) -> Intersection<3> {
+    let cross = |a: &[BigInt; 3], b: &[BigInt; 3]| {
+        [
+            &a[1] * &b[2] - &a[2] * &b[1],
+            &a[2] * &b[0] - &a[0] * &b[2],
+            &a[0] * &b[1] - &a[1] * &b[0],
+        ]
+    };
+    let dot =
+        |a: &[BigInt; 3], b: &[BigInt; 3]| -> BigInt { a.iter().zip(b).map(|(a, b)| a * b).sum() };
+
+    let delta = [0, 1, 2].map(|i| BigInt::from(p2[i]) - BigInt::from(p1[i]));
+    let (d1_big, d2_big) = (d1.map(BigInt::from), d2.map(BigInt::from));
+
+    let normal = cross(&d1_big, &d2_big);
+    let normal_len2 = dot(&normal, &normal);
+
+    if normal_len2.is_zero() {
+        // parallel lines, check if p2 lies on the first line
+        return if cross(&delta, &d1_big).iter().all(Zero::is_zero) {
+            Intersection::Coincident
+        } else {
+            Intersection::None
+        };
+    }
+
+    // lines that aren't coplanar are skew
+    if !dot(&delta, &normal).is_zero() {
+        return Intersection::None;
+    }
+
+    let t = BigRational::new(dot(&cross(&delta, &d2_big), &normal), normal_len2.clone());
+    let s = BigRational::new(dot(&cross(&delta, &d1_big), &normal), normal_len2);
+    let point = [0, 1, 2].map(|i| rational(p1[i]) + &t * rational(d1[i]));
+
+    Intersection::Point { point, t, s }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(n: i128) -> BigRational {
+        rational(n)
+    }
+
+    fn frac(n: i64, d: i64) -> BigRational {
+        BigRational::new(n.into(), d.into())
+    }
+
+    #[test]
+    pub fn test_determinant() {
+        let m = Matrix::from_rows(vec![vec![2, -3, 1], vec![2, 0, -1], vec![1, 4, 5]]);
+        assert_eq!(m.determinant(), Some(49));
+        assert_eq!(m.to_rational().determinant(), r(49));
+
+        let singular = Matrix::from_rows(vec![vec![1, 2], vec![2, 4]]);
+        assert_eq!(singular.determinant(), Some(0));
+
+        let swapped = Matrix::from_rows(vec![vec![0, 1], vec![1, 0]]);
+        assert_eq!(swapped.determinant(), Some(-1));
+        assert_eq!(swapped.to_rational().determinant(), r(-1));
+
+        let huge = Matrix::from_rows(vec![vec![i128::MAX, 1], vec![1, i128::MAX]]);
+        assert_eq!(huge.determinant(), None);
+    }
+
+    #[test]
+    pub fn test_solve() {
+        // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+        let m = Matrix::from_rows(vec![vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]]);
+        let b = [8, -11, -3].map(r);
+        assert_eq!(m.to_rational().solve(&b), Some(vec![r(2), r(3), r(-1)]));
+
+        let m = Matrix::from_rows(vec![vec![3, 0], vec![0, 2]]);
+        assert_eq!(
+            m.to_rational().solve(&[r(1), r(1)]),
+            Some(vec![frac(1, 3), frac(1, 2)])
+        );
+
+        let singular = Matrix::from_rows(vec![vec![1, 2], vec![2, 4]]).to_rational();
+        assert_eq!(singular.solve(&[r(1), r(2)]), None); // infinitely many solutions
+        assert_eq!(singular.solve(&[r(1), r(3)]), None); // no solutions
+    }
+
+    #[test]
+    pub fn test_intersect_2d() {
+        let Intersection::Point { point, t, s } =
+            intersect_lines_2d([19, 13], [-2, 1], [18, 19], [-1, -1])
+        else {
+            panic!("lines should intersect");
+        };
+        assert_eq!(point, [frac(43, 3), frac(46, 3)]);
+        assert_eq!(t, frac(7, 3));
+        assert_eq!(s, frac(11, 3));
+
+        let parallel = intersect_lines_2d([0, 0], [1, 1], [1, 0], [-2, -2]);
+        assert_eq!(parallel, Intersection::None);
+
+        let same = intersect_lines_2d([0, 0], [1, 1], [3, 3], [2, 2]);
+        assert_eq!(same, Intersection::Coincident);
+    }
+
+    #[test]
+    pub fn test_intersect_3d() {
+        let Intersection::Point { point, t, s } =
+            intersect_lines_3d([0, 0, 0], [1, 1, 1], [2, 0, 2], [0, 1, 0])
+        else {
+            panic!("lines should intersect");
+        };
+        assert_eq!(point, [r(2), r(2), r(2)]);
+        assert_eq!(t, r(2));
+        assert_eq!(s, r(2));
+
+        let skew = intersect_lines_3d([0, 0, 0], [1, 0, 0], [0, 1, 1], [0, 1, 0]);
+        assert_eq!(skew, Intersection::None);
+
+        let same = intersect_lines_3d([1, 2, 3], [1, 1, 1], [0, 1, 2], [-3, -3, -3]);
+        assert_eq!(same, Intersection::Coincident);
+    }
+
+    #[test]
+    pub fn test_intersect_extreme() {
+        // the lines start at opposite ends of the i128 range, so p2 - p1 doesn't fit in an i128
+        let span = r(i128::MAX) - r(i128::MIN);
+
+        let Intersection::Point { point, t, s } =
+            intersect_lines_2d([i128::MIN, 0], [1, 0], [i128::MAX, 5], [0, 1])
+        else {
+            panic!("lines should intersect");
+        };
+        assert_eq!(point, [r(i128::MAX), r(0)]);
+        assert_eq!(t, span);
+        assert_eq!(s, r(-5));
+
+        let Intersection::Point { point, t, s } =
+            intersect_lines_3d([i128::MIN, 0, 0], [1, 0, 0], [i128::MAX, 0, 7], [0, 0, 1])
+        else {
+            panic!("lines should intersect");
+        };
+        assert_eq!(point, [r(i128::MAX), r(0), r(0)]);
+        assert_eq!(t, span);
+        assert_eq!(s, r(-7));
+    }
+}
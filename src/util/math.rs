@@ -0,0 +1,175 @@
+//! Number theory helpers, e.g. for puzzles with cycles that need to line up
+
+/// Error returned by the CRT solvers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    /// The congruences contradict each other.
+    NoSolution,
+
+    /// An intermediate value did not fit in the integer type.
+    Overflow,
+}
+
+/// Extended euclidean algorithm.
+///
+/// Returns `(g, x, y)` such that `a*x + b*y = g`, where `g = gcd(a, b)` is non-negative.
+///
+/// The results are i128s, since they don't always fit in an i64: `ext_gcd(i64::MIN, 0)` has a gcd
+/// of 2^63, for example.
+pub fn ext_gcd(a: i64, b: i64) -> (i128, i128, i128) {
+    // the bezout coefficients are bounded by |b/g| and |a/g|, so nothing gets near the i128 limits
+    ext_gcd_i128(a.into(), b.into()).expect("can't overflow for i64 inputs")
+}
+
+/// Overflow-checked version of [ext_gcd].
+pub fn ext_gcd_i128(a: i128, b: i128) -> Option<(i128, i128, i128)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+
+    while r != 0 {
+        let q = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(q.checked_mul(r)?)?);
+        (old_x, x) = (x, old_x.checked_sub(q.checked_mul(x)?)?);
+        (old_y, y) = (y, old_y.checked_sub(q.checked_mul(y)?)?);
+    }
+
+    if old_r < 0 {
+        Some((
+            old_r.checked_neg()?,
+            old_x.checked_neg()?,
+            old_y.checked_neg()?,
+        ))
+    } else {
+        Some((old_r, old_x, old_y))
+    }
+}
+
+/// Calculate the inverse of `a` modulo `m`, if it exists.
+///
+/// The returned value is in the range `0..m`.
+pub fn mod_inv(a: i64, m: i64) -> Option<i64> {
+    mod_inv_i128(a.into(), m.into()).map(|n| n as i64)
+}
+
+/// Overflow-checked version of [mod_inv].
+pub fn mod_inv_i128(a: i128, m: i128) -> Option<i128> {
+    assert!(m > 0, "modulus must be positive");
+    let (g, x, _) = ext_gcd_i128(a.rem_euclid(m), m)?;
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Solve a system of congruences `x ≡ r (mod m)` given as `(r, m)` pairs.
+///
+/// The moduli do not have to be coprime. Returns `(x, lcm)` where `x` is the smallest
+/// non-negative solution, and all solutions are of the form `x + k*lcm`.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Result<(i64, i64), CrtError> {
+    let (x, m) = crt_i128(
+        congruences
+            .into_iter()
+            .map(|(r, m)| (i128::from(r), i128::from(m))),
+    )?;
+
+    let narrow = |n: i128| i64::try_from(n).map_err(|_| CrtError::Overflow);
+    Ok((narrow(x)?, narrow(m)?))
+}
+
+/// Overflow-checked version of [crt].
+pub fn crt_i128(
+    congruences: impl IntoIterator<Item = (i128, i128)>,
+) -> Result<(i128, i128), CrtError> {
+    use CrtError::*;
+
+    congruences
+        .into_iter()
+        .try_fold((0i128, 1i128), |(r1, m1), (r2, m2)| {
+            assert!(m2 > 0, "modulus must be positive");
+            let r2 = r2.rem_euclid(m2);
+
+            let (g, p, _) = ext_gcd_i128(m1, m2).ok_or(Overflow)?;
+            let diff = r2 - r1;
+            if diff % g != 0 {
+                return Err(NoSolution);
+            }
+
+            let m2_g = m2 / g;
+            let lcm = (m1 / g).checked_mul(m2).ok_or(Overflow)?;
+
+            // solve m1*k ≡ diff (mod m2) for k
+            let k = (diff / g).rem_euclid(m2_g);
+            let k = k.checked_mul(p.rem_euclid(m2_g)).ok_or(Overflow)? % m2_g;
+
+            let x = m1.checked_mul(k).and_then(|n| n.checked_add(r1));
+            let x = x.ok_or(Overflow)?.rem_euclid(lcm);
+
+            Ok((x, lcm))
+        })
+}
+
+/// Find the first point in time where a set of cycles line up.
+///
+/// Each cycle is given as `(offset, period)`, and is "active" at `offset + k*period` for all
+/// `k >= 0`. Returns the smallest time where all of the cycles are active at once.
+pub fn align_cycles(cycles: impl IntoIterator<Item = (i64, i64)>) -> Result<i64, CrtError> {
+    let mut min_time = 0;
+    let (x, lcm) = crt(cycles.into_iter().inspect(|&(offset, _)| {
+        min_time = min_time.max(offset);
+    }))?;
+
+    if x >= min_time {
+        return Ok(x);
+    }
+
+    let cycles_behind = (min_time - x + lcm - 1) / lcm;
+    cycles_behind
+        .checked_mul(lcm)
+        .and_then(|n| n.checked_add(x))
+        .ok_or(CrtError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_ext_gcd() {
+        assert_eq!(ext_gcd(240, 46), (2, -9, 47));
+        assert_eq!(ext_gcd(-12, 18).0, 6);
+        assert_eq!(ext_gcd(0, 0), (0, 1, 0));
+        assert_eq!(ext_gcd(i64::MIN, 0), (1 << 63, -1, 0));
+        assert_eq!(ext_gcd(0, i64::MIN), (1 << 63, 0, -1));
+        assert_eq!(ext_gcd(i64::MIN, i64::MIN).0, 1 << 63);
+        assert_eq!(ext_gcd(i64::MIN, i64::MAX), (1, -1, -1));
+        assert_eq!(ext_gcd_i128(i128::MIN, -1), None);
+    }
+
+    #[test]
+    pub fn test_mod_inv() {
+        assert_eq!(mod_inv(3, 11), Some(4));
+        assert_eq!(mod_inv(-3, 11), Some(7));
+        assert_eq!(mod_inv(6, 9), None);
+    }
+
+    #[test]
+    pub fn test_crt() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+
+        // non-coprime moduli
+        assert_eq!(crt([(2, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), Err(CrtError::NoSolution));
+
+        assert_eq!(
+            crt([(0, i64::MAX), (1, i64::MAX - 1)]),
+            Err(CrtError::Overflow)
+        );
+        assert!(crt_i128([(0, i64::MAX.into()), (1, (i64::MAX - 1).into())]).is_ok());
+    }
+
+    #[test]
+    pub fn test_align_cycles() {
+        assert_eq!(align_cycles([(2, 2), (3, 3)]), Ok(6));
+        assert_eq!(align_cycles([(5, 3), (1, 4)]), Ok(5));
+        assert_eq!(align_cycles([(10, 4), (0, 2)]), Ok(10));
+        assert_eq!(align_cycles([]), Ok(0));
+    }
+}
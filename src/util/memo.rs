@@ -0,0 +1,152 @@
+//! Caching for recursive dynamic-programming solutions
+
+use super::HashMap;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// A cache of results for a recursive function
+///
+/// The function should take the [Memo] as an argument, and recurse through
+/// [Memo::get_or_insert_with]:
+///
+/// ```ignore
+/// fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+///     memo.get_or_insert_with(&n, |memo| match n {
+///         0 | 1 => n,
+///         _ => fib(memo, n - 1) + fib(memo, n - 2),
+///     })
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: MemoStats,
+}
+
+/// Cache statistics for a [Memo]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Number of lookups that were answered by the cache.
+    pub hits: usize,
+
+    /// Number of lookups where the value had to be computed.
+    pub misses: usize,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            cache: HashMap::default(),
+            stats: MemoStats::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the cached value for `key`, or compute and cache it with `f`.
+    ///
+    /// `key` may be a borrowed form of the key type, e.g. a `&[u8]` for a `Vec<u8>` key, in which
+    /// case it is only converted to an owned value on a cache miss.
+    ///
+    /// `f` gets access to the [Memo] so that it can recurse.
+    pub fn get_or_insert_with<Q>(&mut self, key: &Q, f: impl FnOnce(&mut Self) -> V) -> V
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K> + Hash + Eq + ?Sized,
+    {
+        if let Some(value) = self.cache.get(key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+
+        self.stats.misses += 1;
+        let value = f(self);
+        self.cache.insert(key.to_owned(), value.clone());
+        value
+    }
+
+    /// Get the cached value for `key`, if any. This does not count towards the stats.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.cache.get(key)
+    }
+
+    /// Number of cached values.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    /// Clear all cached values, and reset the stats.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        memo.get_or_insert_with(&n, |memo| match n {
+            0 | 1 => n,
+            _ => fib(memo, n - 1) + fib(memo, n - 2),
+        })
+    }
+
+    /// Count the ways to split `s` into words from `words`
+    fn count_splits(memo: &mut Memo<String, u64>, words: &[&str], s: &str) -> u64 {
+        if s.is_empty() {
+            return 1;
+        }
+
+        memo.get_or_insert_with(s, |memo| {
+            words
+                .iter()
+                .filter_map(|word| s.strip_prefix(word))
+                .map(|rest| count_splits(memo, words, rest))
+                .sum()
+        })
+    }
+
+    #[test]
+    pub fn test_fib() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(&mut memo, 90), 2880067194370816120);
+        assert_eq!(memo.len(), 91);
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                hits: 88,
+                misses: 91
+            }
+        );
+
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+
+    #[test]
+    pub fn test_borrowed_keys() {
+        let mut memo = Memo::new();
+        let words = ["a", "ab", "b", "ba"];
+        assert_eq!(count_splits(&mut memo, &words, "abab"), 5);
+        assert_eq!(memo.get("bab"), Some(&3));
+    }
+}
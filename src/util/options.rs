@@ -0,0 +1,46 @@
+//! Extra options for individual days, passed through environment variables
+//!
+//! The command line interface is generated by `aoc_macro`, so days can't add flags of their own.
+//! Instead, option `name` of a day is read from the variable `AOC_<DAY>_<NAME>`:
+//!
+//! ```sh
+//! AOC_DAY01_VOCABULARY=swedish cargo run -- day01 part2
+//! ```
+
+use std::env;
+
+/// Name of the environment variable for an option.
+///
+/// `module` is the module path of the day, i.e. `module_path!()`.
+pub fn var_name(module: &str, name: &str) -> String {
+    let day = module.rsplit("::").next().unwrap_or(module);
+    format!("AOC_{day}_{name}")
+        .to_ascii_uppercase()
+        .replace('-', "_")
+}
+
+/// Get the value of an option, if it's set.
+///
+/// Panics if the value isn't valid unicode, since that's most likely a mistake.
+pub fn get(module: &str, name: &str) -> Option<String> {
+    let var = var_name(module, name);
+    match env::var(&var) {
+        Ok(value) => Some(value),
+        Err(env::VarError::NotPresent) => None,
+        Err(env::VarError::NotUnicode(_)) => panic!("{var} is not valid unicode"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_var_name() {
+        assert_eq!(
+            var_name("aoc_2023::day01", "vocabulary"),
+            "AOC_DAY01_VOCABULARY"
+        );
+        assert_eq!(var_name("day02", "bag-limits"), "AOC_DAY02_BAG_LIMITS");
+    }
+}
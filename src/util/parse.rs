@@ -0,0 +1,343 @@
+//! A tiny zero-copy parser for puzzle inputs
+//!
+//! ```ignore
+//! let game = Parser::new("Game 1: 3 blue, 4 red").all(|p| {
+//!     p.lit("Game ")?;
+//!     let id: u64 = p.int()?;
+//!     p.lit(": ")?;
+//!     let cubes = p.sep_by(", ", |p| {
+//!         let n: u64 = p.int()?;
+//!         p.lit(" ")?;
+//!         Ok((n, p.alnum()?))
+//!     })?;
+//!     Ok((id, cubes))
+//! });
+//! ```
+
+use std::any::type_name;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A cursor into the input string
+#[derive(Debug, Clone, Copy)]
+pub struct Parser<'a> {
+    /// The entire input string, so that we can report line numbers on errors.
+    src: &'a str,
+
+    /// Current byte offset into `src`.
+    pos: usize,
+
+    /// Byte offset into `src` where this parser must stop.
+    end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the input where the error occurred.
+    pub pos: usize,
+
+    /// 1-indexed line number.
+    pub line: usize,
+
+    /// 1-indexed column number, in chars.
+    pub column: usize,
+
+    /// Description of what the parser was looking for.
+    pub expected: String,
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ParseError {
+            line,
+            column,
+            expected,
+            ..
+        } = self;
+        write!(
+            f,
+            "parse error at line {line}, column {column}: expected {expected}"
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<'a> Parser<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Parser {
+            src,
+            pos: 0,
+            end: src.len(),
+        }
+    }
+
+    /// Run a parser over the entire input. Fails if there's unparsed input left, apart from a
+    /// trailing newline.
+    pub fn all<T>(mut self, f: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let out = f(&mut self)?;
+        if self.rest() == "\n" {
+            self.pos += 1;
+        }
+        self.eof()?;
+        Ok(out)
+    }
+
+    /// The remaining unparsed input.
+    pub fn rest(&self) -> &'a str {
+        &self.src[self.pos..self.end]
+    }
+
+    /// Construct an error at the current position.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        let before = &self.src[..self.pos];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+        ParseError {
+            pos: self.pos,
+            line: 1 + before.matches('\n').count(),
+            column: 1 + before[line_start..].chars().count(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Succeed only if there is no input left.
+    pub fn eof(&self) -> ParseResult<()> {
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of input"))
+        }
+    }
+
+    /// Consume a literal string.
+    pub fn lit(&mut self, lit: &str) -> ParseResult<&'a str> {
+        if self.rest().starts_with(lit) {
+            Ok(self.advance(lit.len()))
+        } else {
+            Err(self.error(format!("{lit:?}")))
+        }
+    }
+
+    /// Consume one of a set of literal strings, and return the associated value.
+    ///
+    /// Literals are tried in order, so if one is a prefix of another, put the longer one first.
+    pub fn one_of<T: Copy>(&mut self, options: &[(&str, T)]) -> ParseResult<T> {
+        for &(lit, value) in options {
+            if self.lit(lit).is_ok() {
+                return Ok(value);
+            }
+        }
+
+        let options: Vec<_> = options.iter().map(|(lit, _)| format!("{lit:?}")).collect();
+        Err(self.error(format!("one of {}", options.join(", "))))
+    }
+
+    /// Consume a single char and convert it to `T`, e.g. an enum deriving `CharEnum`.
+    pub fn char<T: TryFrom<char>>(&mut self) -> ParseResult<T> {
+        if let Some(c) = self.rest().chars().next() {
+            if let Ok(value) = T::try_from(c) {
+                self.advance(c.len_utf8());
+                return Ok(value);
+            }
+        }
+
+        let name = type_name::<T>().rsplit("::").next().unwrap_or("char");
+        Err(self.error(format!("a valid {name}")))
+    }
+
+    /// Consume chars as long as they match `pred`. May return an empty string.
+    pub fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let len = self.rest().find(|c| !pred(c)).unwrap_or(self.rest().len());
+        self.advance(len)
+    }
+
+    /// Consume everything up until (but not including) `delim`.
+    pub fn take_until(&mut self, delim: &str) -> ParseResult<&'a str> {
+        match self.rest().find(delim) {
+            Some(len) => Ok(self.advance(len)),
+            None => Err(self.error(format!("{delim:?}"))),
+        }
+    }
+
+    /// Consume a non-empty run of alphanumeric chars.
+    pub fn alnum(&mut self) -> ParseResult<&'a str> {
+        match self.take_while(char::is_alphanumeric) {
+            "" => Err(self.error("alphanumeric characters")),
+            s => Ok(s),
+        }
+    }
+
+    /// Consume an integer, with an optional leading `-`.
+    pub fn int<T: FromStr>(&mut self) -> ParseResult<T> {
+        let start = *self;
+        let sign = self.rest().starts_with('-') as usize;
+        let len = sign
+            + self.rest()[sign..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(self.rest().len() - sign);
+
+        match self.rest()[..len].parse() {
+            Ok(n) => {
+                self.advance(len);
+                Ok(n)
+            }
+            Err(_) => Err(start.error("an integer")),
+        }
+    }
+
+    /// Parse one or more items separated by `sep`.
+    pub fn sep_by<T>(
+        &mut self,
+        sep: &str,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.lit(sep).is_ok() {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Parse zero or more items, until `item` fails.
+    ///
+    /// The failing attempt is rolled back, and its error is discarded.
+    pub fn many<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = vec![];
+        loop {
+            let mut attempt = *self;
+            match item(&mut attempt) {
+                Ok(t) => {
+                    *self = attempt;
+                    items.push(t);
+                }
+                Err(_) => return Ok(items),
+            }
+        }
+    }
+
+    /// Parse every remaining line with `line`. Each line must be consumed entirely.
+    pub fn lines<T>(
+        &mut self,
+        line: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        self.split_with("\n", line)
+    }
+
+    /// Parse every remaining block of lines with `block`. Blocks are separated by blank lines,
+    /// and each block must be consumed entirely.
+    pub fn blocks<T>(
+        &mut self,
+        block: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        self.split_with("\n\n", block)
+    }
+
+    /// Split the remaining input on `delim`, and run `f` on each part.
+    fn split_with<T>(
+        &mut self,
+        delim: &str,
+        mut f: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = vec![];
+
+        // ignore a trailing newline
+        let end = match self.rest().strip_suffix('\n') {
+            Some(rest) => self.pos + rest.len(),
+            None => self.end,
+        };
+
+        while self.pos < end {
+            let len = self.src[self.pos..end]
+                .find(delim)
+                .unwrap_or(end - self.pos);
+            let mut part = Parser {
+                src: self.src,
+                pos: self.pos,
+                end: self.pos + len,
+            };
+
+            items.push(f(&mut part)?);
+            part.eof()?;
+
+            self.pos = (part.end + delim.len()).min(end);
+        }
+
+        self.pos = self.end;
+        Ok(items)
+    }
+
+    /// Consume `len` bytes, and return them.
+    fn advance(&mut self, len: usize) -> &'a str {
+        let s = &self.rest()[..len];
+        self.pos += len;
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_parse() {
+        let input = "a: 1, -2, 3\nbb: 4\n";
+        let parsed = Parser::new(input).all(|p| {
+            p.lines(|p| {
+                let name = p.alnum()?;
+                p.lit(": ")?;
+                Ok((name, p.sep_by(", ", Parser::int::<i32>)?))
+            })
+        });
+
+        assert_eq!(parsed, Ok(vec![("a", vec![1, -2, 3]), ("bb", vec![4])]));
+    }
+
+    #[test]
+    pub fn test_blocks() {
+        let input = "LRL\n\nx\ny\n\nz\n";
+        let parsed = Parser::new(input).all(|p| {
+            let dirs = p.many(|p| p.one_of(&[("L", 'l'), ("R", 'r')]))?;
+            p.lit("\n\n")?;
+            let blocks = p.blocks(|p| p.lines(Parser::alnum))?;
+            Ok((dirs, blocks))
+        });
+
+        let expected = (vec!['l', 'r', 'l'], vec![vec!["x", "y"], vec!["z"]]);
+        assert_eq!(parsed, Ok(expected));
+    }
+
+    #[test]
+    pub fn test_char() {
+        let mut p = Parser::new("a€");
+        assert_eq!(p.char::<u8>(), Ok(b'a'));
+        assert_eq!(p.char::<u8>().unwrap_err().expected, "a valid u8");
+        assert_eq!(p.char::<char>(), Ok('€'));
+        assert!(p.char::<char>().is_err());
+    }
+
+    #[test]
+    pub fn test_error_position() {
+        let input = "1 2\n3 x 4\n";
+        let err = Parser::new(input)
+            .all(|p| p.lines(|p| p.sep_by(" ", Parser::int::<u8>)))
+            .unwrap_err();
+
+        assert_eq!((err.pos, err.line, err.column), (6, 2, 3));
+        assert_eq!(err.expected, "an integer");
+
+        let err = Parser::new("1 2 ").all(|p| p.sep_by(" ", Parser::int::<u8>));
+        assert_eq!(err.unwrap_err().pos, 4);
+
+        let err = Parser::new("ab").all(|p| p.lit("a")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error at line 1, column 2: expected end of input"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Polynomial interpolation and extrapolation of sequences
+
+use super::checked::CheckedOps;
+use num::{BigInt, BigRational, Zero};
+
+/// A polynomial fitted to integer samples at `x = 0, 1, 2, ...`
+///
+/// Stored in Newton's forward difference form, `f(x) = Σ Δᵏf(0) * C(x, k)`, which lets us evaluate
+/// it at any integer `x` using only exact integer arithmetic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly {
+    /// The leading forward differences, `Δᵏf(0)`, without trailing zeroes.
+    differences: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitError {
+    /// There are not enough samples to tell if the sequence has the requested degree.
+    TooFewSamples,
+
+    /// The sequence isn't a polynomial of at most this degree.
+    NotPolynomial { degree: usize },
+}
+
+/// Build the table of finite differences of a sequence.
+///
+/// The first row is the sequence itself, and each following row is the differences between
+/// consecutive elements of the row before it. Stops after the first row of only zeroes, or when
+/// running out of elements. Differences that overflow an i64 are reported with the `checked`
+/// feature.
+pub fn difference_table(samples: &[i64]) -> Vec<Vec<i64>> {
+    let mut table = vec![samples.to_vec()];
+    loop {
+        let last = table.last().unwrap();
+        if last.is_empty() || last.iter().all(|&n| n == 0) {
+            break;
+        }
+
+        let next = last.array_windows().map(|[a, b]| b.sub_ck(*a)).collect();
+        table.push(next);
+    }
+    table
+}
+
+impl Poly {
+    /// Fit a polynomial of the lowest possible degree to the samples.
+    ///
+    /// If the differences never reach zero, this is the polynomial of degree `len - 1` that goes
+    /// through all of the samples.
+    pub fn fit(samples: &[i64]) -> Self {
+        let mut differences: Vec<i64> = difference_table(samples)
+            .into_iter()
+            .filter_map(|row| row.first().copied())
+            .collect();
+
+        while differences.last() == Some(&0) {
+            differences.pop();
+        }
+
+        Poly { differences }
+    }
+
+    /// Fit a polynomial of at most `max_degree` to the samples, and verify that it matches all
+    /// of them.
+    pub fn fit_degree(samples: &[i64], max_degree: usize) -> Result<Self, FitError> {
+        let table = difference_table(samples);
+        let zero_row = table
+            .iter()
+            .position(|row| !row.is_empty() && row.iter().all(|&n| n == 0));
+
+        match zero_row {
+            Some(row) if row <= max_degree + 1 => Ok(Self::fit(samples)),
+            None if samples.len() < max_degree + 2 => Err(FitError::TooFewSamples),
+            _ => Err(FitError::NotPolynomial { degree: max_degree }),
+        }
+    }
+
+    /// The degree of the polynomial, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    /// Evaluate the polynomial at `x`, which may be outside of the sampled range.
+    ///
+    /// Panics on overflow.
+    pub fn eval(&self, x: i64) -> i64 {
+        self.checked_eval(x)
+            .and_then(|y| i64::try_from(y).ok())
+            .unwrap_or_else(|| panic!("overflow evaluating polynomial at {x}"))
+    }
+
+    /// Evaluate the polynomial at `x`. Returns `None` on overflow.
+    pub fn checked_eval(&self, x: i64) -> Option<i128> {
+        let x = i128::from(x);
+        let mut y = 0i128;
+
+        // generalized binomial coefficient C(x, k), which is an integer even for negative x
+        let mut binomial = 1i128;
+
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                let k = k as i128;
+                binomial = binomial.checked_mul(x - k + 1)? / k;
+            }
+            y = y.checked_add(binomial.checked_mul(difference.into())?)?;
+        }
+
+        Some(y)
+    }
+}
+
+/// Evaluate the lagrange polynomial going through `points` at `x`, using exact rationals.
+///
+/// The x-coordinates of the points must be distinct.
+pub fn lagrange(points: &[(BigRational, BigRational)], x: &BigRational) -> BigRational {
+    let mut y = BigRational::zero();
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut term = yi.clone();
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                assert_ne!(xi, xj, "x-coordinates must be distinct");
+                term *= (x - xj) / (xi - xj);
+            }
+        }
+        y += term;
+    }
+    y
+}
+
+/// Like [lagrange], but for integer points.
+pub fn lagrange_i64(points: &[(i64, i64)], x: i64) -> BigRational {
+    let to_rational = |n: i64| BigRational::from(BigInt::from(n));
+    let points: Vec<_> = points
+        .iter()
+        .map(|&(x, y)| (to_rational(x), to_rational(y)))
+        .collect();
+    lagrange(&points, &to_rational(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_extrapolate() {
+        let poly = Poly::fit(&[10, 13, 16, 21, 30, 45]);
+        assert_eq!(poly.degree(), Some(3));
+        assert_eq!(poly.eval(6), 68);
+        assert_eq!(poly.eval(-1), 5);
+        assert_eq!(poly.eval(2), 16);
+
+        let squares: Vec<i64> = (0..5).map(|x| x * x).collect();
+        assert_eq!(Poly::fit(&squares).eval(-1_000_000), 1_000_000_000_000);
+
+        assert_eq!(Poly::fit(&[0, 0, 0]).degree(), None);
+        assert_eq!(Poly::fit(&[]).eval(7), 0);
+
+        assert_eq!(
+            Poly::fit(&[1, i64::MAX]).checked_eval(3),
+            Some(3 * i128::from(i64::MAX) - 2)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "arithmetic overflow")]
+    pub fn test_difference_overflow() {
+        difference_table(&[i64::MIN, i64::MAX]);
+    }
+
+    #[test]
+    pub fn test_fit_degree() {
+        let squares = [0, 1, 4, 9, 16];
+        assert!(Poly::fit_degree(&squares, 2).is_ok());
+        assert!(Poly::fit_degree(&squares, 3).is_ok());
+        assert_eq!(
+            Poly::fit_degree(&squares, 1),
+            Err(FitError::NotPolynomial { degree: 1 })
+        );
+        assert_eq!(
+            Poly::fit_degree(&[1, 2, 4, 8, 16], 2),
+            Err(FitError::NotPolynomial { degree: 2 })
+        );
+        assert_eq!(
+            Poly::fit_degree(&[0, 1, 4], 2),
+            Err(FitError::TooFewSamples)
+        );
+    }
+
+    #[test]
+    pub fn test_lagrange() {
+        let points = [(0, 1), (2, 5), (5, 26)]; // x² + 1
+        assert_eq!(
+            lagrange_i64(&points, 10),
+            BigRational::from(BigInt::from(101))
+        );
+
+        let half = BigRational::new(1.into(), 2.into());
+        let points = [
+            (half.clone(), half.clone()),
+            (BigRational::zero(), BigRational::zero()),
+        ];
+        let y = lagrange(&points, &BigRational::new(1.into(), 3.into())); // y = x
+        assert_eq!(y, BigRational::new(1.into(), 3.into()));
+    }
+}
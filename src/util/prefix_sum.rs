@@ -0,0 +1,223 @@
+//! Prefix sums for constant time range sums, and difference arrays for cheap range updates
+//!
+//! All arithmetic goes through [CheckedOps], so overflows are reported with the `checked`
+//! feature.
+
+use super::checked::CheckedOps;
+use super::grid::Grid;
+use std::ops::{Bound, Range, RangeBounds};
+
+/// Convert a range to `start..end`, and check that it's within `0..len`.
+fn to_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "range {start}..{end} out of bounds for length {len}"
+    );
+    start..end
+}
+
+/// Sums of all prefixes of a sequence, for summing any range in O(1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSum<T> {
+    /// `sums[i]` is the sum of the first `i` values.
+    sums: Vec<T>,
+}
+
+impl<T: CheckedOps> PrefixSum<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut sums = vec![T::ZERO];
+        let mut sum = T::ZERO;
+        for value in values {
+            sum = sum.add_ck(value);
+            sums.push(sum);
+        }
+        PrefixSum { sums }
+    }
+
+    /// Number of values.
+    pub fn len(&self) -> usize {
+        self.sums.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sum the values in `range`.
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let Range { start, end } = to_range(range, self.len());
+        self.sums[end].sub_ck(self.sums[start])
+    }
+}
+
+/// Sums of all rectangles starting in the top left corner of a grid (a summed-area table), for
+/// summing any rectangle in O(1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSum2D<T> {
+    width: usize,
+    height: usize,
+
+    /// `sums[y * (width + 1) + x]` is the sum of the `x` by `y` rectangle in the top left corner.
+    sums: Vec<T>,
+}
+
+impl<T: CheckedOps> PrefixSum2D<T> {
+    pub fn new(grid: &Grid<T>) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let stride = width + 1;
+        let mut sums = vec![T::ZERO; stride * (height + 1)];
+
+        for (y, row) in grid.rows().enumerate() {
+            let mut row_sum = T::ZERO;
+            for (x, &value) in row.iter().enumerate() {
+                row_sum = row_sum.add_ck(value);
+                let above = sums[y * stride + x + 1];
+                sums[(y + 1) * stride + x + 1] = above.add_ck(row_sum);
+            }
+        }
+
+        PrefixSum2D {
+            width,
+            height,
+            sums,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sum the values in the rectangle of columns `xs` and rows `ys`.
+    pub fn sum(&self, xs: impl RangeBounds<usize>, ys: impl RangeBounds<usize>) -> T {
+        let xs = to_range(xs, self.width);
+        let ys = to_range(ys, self.height);
+        let at = |x: usize, y: usize| self.sums[y * (self.width + 1) + x];
+
+        // add before subtracting, so that this works for unsigned types
+        let outer = at(xs.end, ys.end).add_ck(at(xs.start, ys.start));
+        let sides = at(xs.start, ys.end).add_ck(at(xs.end, ys.start));
+        outer.sub_ck(sides)
+    }
+}
+
+/// A sequence stored as the differences between adjacent values, so that adding to a whole range
+/// is O(1)
+///
+/// The values can be read all at once with [DifferenceArray::into_values], or in a single sweep
+/// from left to right by summing up [DifferenceArray::delta]. In the latter case, ranges to the
+/// right of the sweep can still be updated while sweeping.
+///
+/// The deltas can be negative, so `T` should be a signed type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifferenceArray<T> {
+    /// `deltas[i]` is `values[i] - values[i - 1]`. There's an extra element at the end so that
+    /// ranges can end at `len`.
+    deltas: Vec<T>,
+}
+
+impl<T: CheckedOps> DifferenceArray<T> {
+    /// Create a difference array of `len` zeroes.
+    pub fn new(len: usize) -> Self {
+        DifferenceArray {
+            deltas: vec![T::ZERO; len + 1],
+        }
+    }
+
+    /// Number of values.
+    pub fn len(&self) -> usize {
+        self.deltas.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `amount` to every value in `range`.
+    pub fn add(&mut self, range: impl RangeBounds<usize>, amount: T) {
+        let Range { start, end } = to_range(range, self.len());
+        self.deltas[start] = self.deltas[start].add_ck(amount);
+        self.deltas[end] = self.deltas[end].sub_ck(amount);
+    }
+
+    /// The difference between the value at `i` and the one before it.
+    pub fn delta(&self, i: usize) -> T {
+        self.deltas[i]
+    }
+
+    /// Calculate all values.
+    pub fn into_values(mut self) -> Vec<T> {
+        self.deltas.pop();
+        let mut value = T::ZERO;
+        for delta in &mut self.deltas {
+            value = value.add_ck(*delta);
+            *delta = value;
+        }
+        self.deltas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_prefix_sum() {
+        let sums = PrefixSum::new([3u32, 1, 4, 1, 5]);
+        assert_eq!(sums.len(), 5);
+        assert_eq!(sums.sum(..), 14);
+        assert_eq!(sums.sum(1..3), 5);
+        assert_eq!(sums.sum(2..=4), 10);
+        assert_eq!(sums.sum(5..), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "range 2..6 out of bounds for length 5")]
+    pub fn test_prefix_sum_bounds() {
+        PrefixSum::new([3u32, 1, 4, 1, 5]).sum(2..6);
+    }
+
+    #[test]
+    pub fn test_prefix_sum_2d() {
+        let grid = Grid::parse("123\n456\n789\n", |b| u64::from(b - b'0'));
+        let sums = PrefixSum2D::new(&grid);
+        assert_eq!(sums.sum(.., ..), 45);
+        assert_eq!(sums.sum(1..3, 1..3), 5 + 6 + 8 + 9);
+        assert_eq!(sums.sum(1..2, ..), 2 + 5 + 8);
+        assert_eq!(sums.sum(..1, 2..), 7);
+        assert_eq!(sums.sum(1..1, ..), 0);
+    }
+
+    #[test]
+    pub fn test_difference_array() {
+        let mut diffs = DifferenceArray::new(5);
+        diffs.add(1..3, 2i64);
+        diffs.add(2.., 10);
+        diffs.add(..=0, 1);
+
+        // sweeping from left to right gives the same result as into_values
+        let mut value = 0;
+        let swept: Vec<_> = (0..diffs.len())
+            .map(|i| {
+                value += diffs.delta(i);
+                value
+            })
+            .collect();
+
+        assert_eq!(diffs.into_values(), [1, 2, 12, 10, 10]);
+        assert_eq!(swept, [1, 2, 12, 10, 10]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Fast extraction of integers from raw bytes
+
+use std::array;
+
+/// Error returned when scanning for integers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanError {
+    /// The integer starting at byte offset `pos` doesn't fit in an i64.
+    Overflow { pos: usize },
+
+    /// Expected to find `expected` integers, but found `found`.
+    Count { expected: usize, found: usize },
+}
+
+/// Iterator over all the integers in a byte string. See [ints].
+#[derive(Debug, Clone)]
+pub struct Ints<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+/// Find all signed integers in a byte string, in one pass, skipping anything that isn't a number.
+///
+/// A `-` directly in front of a number is treated as a sign, unless it is preceded by a letter or
+/// a digit. i.e. "-1, -2" yields `[-1, -2]`, but "1-2" and "x-2" yields `[1, 2]`.
+pub fn ints(b: &(impl AsRef<[u8]> + ?Sized)) -> Ints<'_> {
+    Ints {
+        bytes: b.as_ref(),
+        pos: 0,
+    }
+}
+
+/// Extract exactly `N` signed integers from a byte string. See [ints].
+pub fn ints_array<const N: usize>(b: &(impl AsRef<[u8]> + ?Sized)) -> Result<[i64; N], ScanError> {
+    let mut ints = ints(b);
+
+    let out =
+        array::try_from_fn(|found| ints.next().ok_or(ScanError::Count { expected: N, found })?)?;
+
+    match ints.count() {
+        0 => Ok(out),
+        extra => Err(ScanError::Count {
+            expected: N,
+            found: N + extra,
+        }),
+    }
+}
+
+impl Iterator for Ints<'_> {
+    type Item = Result<i64, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let b = self.bytes;
+        let start = self.pos + b[self.pos..].iter().position(|b| b.is_ascii_digit())?;
+
+        let negative = start > 0
+            && b[start - 1] == b'-'
+            && (start < 2 || !b[start - 2].is_ascii_alphanumeric());
+
+        let digits = b[start..]
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(b.len() - start);
+        self.pos = start + digits;
+
+        // accumulate negative numbers as negative, so that we can represent i64::MIN
+        let n = b[start..self.pos].iter().try_fold(0i64, |n, &b| {
+            let digit = i64::from(b - b'0');
+            let n = n.checked_mul(10)?;
+            if negative {
+                n.checked_sub(digit)
+            } else {
+                n.checked_add(digit)
+            }
+        });
+
+        Some(n.ok_or(ScanError::Overflow {
+            pos: start - usize::from(negative),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::{black_box, Bencher};
+
+    #[test]
+    pub fn test_ints() {
+        let found: Vec<_> = ints("Game 12: -3 blue, 4-5 x-6 --7;8").collect();
+        let expected = [12, -3, 4, 5, 6, -7, 8].map(Ok);
+        assert_eq!(found, expected);
+
+        let found: Vec<_> = ints("-9223372036854775808 9223372036854775808 1").collect();
+        let expected = [Ok(i64::MIN), Err(ScanError::Overflow { pos: 21 }), Ok(1)];
+        assert_eq!(found, expected);
+
+        assert_eq!(ints("").next(), None);
+        assert_eq!(ints("no numbers -").next(), None);
+    }
+
+    #[test]
+    pub fn test_ints_array() {
+        assert_eq!(ints_array("50 98 2"), Ok([50, 98, 2]));
+        assert_eq!(ints_array::<0>(""), Ok([]));
+
+        let too_few = ScanError::Count {
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(ints_array::<3>("50 98"), Err(too_few));
+
+        let too_many = ScanError::Count {
+            expected: 1,
+            found: 3,
+        };
+        assert_eq!(ints_array::<1>("50 98 2"), Err(too_many));
+    }
+
+    fn bench_inputs() -> [&'static str; 2] {
+        [
+            include_str!("../day05/test-input"),
+            include_str!("../day09/test-input"),
+        ]
+    }
+
+    #[bench]
+    fn bench_ints(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                for n in ints(black_box(input)) {
+                    black_box(n.unwrap());
+                }
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_str_parse(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                for n in black_box(input)
+                    .split(|c: char| !c.is_ascii_digit() && c != '-')
+                    .filter_map(|s| s.parse::<i64>().ok())
+                {
+                    black_box(n);
+                }
+            }
+        });
+    }
+}
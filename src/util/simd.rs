@@ -0,0 +1,365 @@
+//! Byte scanning with portable SIMD, for splitting lines and finding delimiters and digits
+//!
+//! Everything works on `LANES` bytes at a time, and falls back to the plain byte-by-byte versions
+//! in [scalar] for the tail of the input.
+
+use std::ops::Range;
+use std::simd::prelude::*;
+
+const LANES: usize = 32;
+type Chunk = Simd<u8, LANES>;
+type ChunkMask = Mask<i8, LANES>;
+
+/// Byte-by-byte versions of the scanning functions
+pub mod scalar {
+    pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+        haystack.iter().position(|&b| b == needle)
+    }
+
+    pub fn count_byte(haystack: &[u8], needle: u8) -> usize {
+        haystack.iter().filter(|&&b| b == needle).count()
+    }
+
+    pub fn find_digit(haystack: &[u8]) -> Option<usize> {
+        haystack.iter().position(u8::is_ascii_digit)
+    }
+
+    pub fn find_non_digit(haystack: &[u8]) -> Option<usize> {
+        haystack.iter().position(|b| !b.is_ascii_digit())
+    }
+}
+
+/// Find the first byte where `simd` sets the mask, checking `LANES` bytes at a time. `scalar`
+/// must check the same thing for a single byte.
+#[inline(always)]
+fn find_by(
+    haystack: &[u8],
+    simd: impl Fn(Chunk) -> ChunkMask,
+    scalar: impl Fn(u8) -> bool,
+) -> Option<usize> {
+    let mut chunks = haystack.chunks_exact(LANES);
+    for (i, chunk) in chunks.by_ref().enumerate() {
+        let bits = simd(Chunk::from_slice(chunk)).to_bitmask();
+        if bits != 0 {
+            return Some(i * LANES + bits.trailing_zeros() as usize);
+        }
+    }
+
+    let tail = chunks.remainder();
+    let tail_start = haystack.len() - tail.len();
+    tail.iter().position(|&b| scalar(b)).map(|i| tail_start + i)
+}
+
+fn is_digit(chunk: Chunk) -> ChunkMask {
+    (chunk - Chunk::splat(b'0')).simd_lt(Chunk::splat(10))
+}
+
+/// Find the first occurrence of `needle`.
+pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+    let splat = Chunk::splat(needle);
+    find_by(haystack, |chunk| chunk.simd_eq(splat), |b| b == needle)
+}
+
+/// Count the occurrences of `needle`.
+pub fn count_byte(haystack: &[u8], needle: u8) -> usize {
+    let splat = Chunk::splat(needle);
+    let mut chunks = haystack.chunks_exact(LANES);
+    let simd_count: usize = chunks
+        .by_ref()
+        .map(|chunk| {
+            Chunk::from_slice(chunk)
+                .simd_eq(splat)
+                .to_bitmask()
+                .count_ones() as usize
+        })
+        .sum();
+    simd_count + scalar::count_byte(chunks.remainder(), needle)
+}
+
+/// Find the first ASCII digit.
+pub fn find_digit(haystack: &[u8]) -> Option<usize> {
+    find_by(haystack, is_digit, |b| b.is_ascii_digit())
+}
+
+/// Find the first byte that isn't an ASCII digit.
+pub fn find_non_digit(haystack: &[u8]) -> Option<usize> {
+    find_by(haystack, |chunk| !is_digit(chunk), |b| !b.is_ascii_digit())
+}
+
+/// Iterator over the pieces of a byte string between occurrences of a delimiter. See [split].
+#[derive(Debug, Clone)]
+pub struct Split<'a> {
+    rest: Option<&'a [u8]>,
+    delimiter: u8,
+}
+
+/// Split a byte string on a delimiter, like [slice::split].
+pub fn split(bytes: &[u8], delimiter: u8) -> Split<'_> {
+    Split {
+        rest: Some(bytes),
+        delimiter,
+    }
+}
+
+impl<'a> Iterator for Split<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let rest = self.rest?;
+        match find_byte(rest, self.delimiter) {
+            Some(i) => {
+                self.rest = Some(&rest[i + 1..]);
+                Some(&rest[..i])
+            }
+            None => self.rest.take(),
+        }
+    }
+}
+
+/// Iterator over the lines of a string. See [lines].
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+    rest: &'a str,
+}
+
+/// Split a string into lines, like [str::lines].
+pub fn lines(s: &str) -> Lines<'_> {
+    Lines { rest: s }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        // splitting on an ASCII byte always leaves valid UTF-8 on both sides
+        let (line, rest) = match find_byte(self.rest.as_bytes(), b'\n') {
+            Some(i) => (&self.rest[..i], &self.rest[i + 1..]),
+            None => (self.rest, ""),
+        };
+        self.rest = rest;
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+}
+
+/// Iterator over the runs of ASCII digits in a byte string. See [digit_runs].
+#[derive(Debug, Clone)]
+pub struct DigitRuns<'a> {
+    bytes: &'a [u8],
+
+    /// Start of the block of `LANES` bytes we're currently looking at.
+    block: usize,
+
+    /// Bitmask of the digits in the current block that haven't been returned yet.
+    digits: u64,
+}
+
+/// Find the byte ranges of all maximal runs of ASCII digits, e.g. to locate the numbers in a line.
+///
+/// This pays off when numbers are sparse. On inputs that are mostly short numbers, most of the
+/// time goes to handling each run, and it's no faster than scanning byte by byte.
+pub fn digit_runs(bytes: &[u8]) -> DigitRuns<'_> {
+    DigitRuns {
+        bytes,
+        block: 0,
+        digits: digit_bits(bytes),
+    }
+}
+
+/// Bitmask of which of the next `LANES` bytes are digits. Bytes past the end count as non-digits.
+fn digit_bits(bytes: &[u8]) -> u64 {
+    let chunk = match bytes.get(..LANES) {
+        Some(chunk) => Chunk::from_slice(chunk),
+        None => {
+            let mut padded = [0; LANES];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            Chunk::from_array(padded)
+        }
+    };
+    is_digit(chunk).to_bitmask()
+}
+
+impl DigitRuns<'_> {
+    /// Move on to the next block. Returns false if there are no more blocks.
+    fn next_block(&mut self) -> bool {
+        self.block += LANES;
+        self.digits = 0;
+        if self.block >= self.bytes.len() {
+            return false;
+        }
+        self.digits = digit_bits(&self.bytes[self.block..]);
+        true
+    }
+}
+
+impl Iterator for DigitRuns<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        while self.digits == 0 {
+            if !self.next_block() {
+                return None;
+            }
+        }
+
+        // runs are usually short, so most of the time the whole run is in the current block
+        let offset = self.digits.trailing_zeros() as usize;
+        let start = self.block + offset;
+        let mut run_end = offset + (self.digits >> offset).trailing_ones() as usize;
+        while run_end == LANES {
+            if !self.next_block() {
+                return Some(start..self.bytes.len());
+            }
+            run_end = self.digits.trailing_ones() as usize;
+        }
+
+        self.digits &= u64::MAX << run_end;
+        Some(start..self.block + run_end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::{black_box, Bencher};
+
+    /// A line that's long enough to hit both the SIMD and the scalar code paths.
+    const LONG: &str = "the quick brown fox jumps over the lazy dog, 1234 times. 5678 more!\n";
+
+    #[test]
+    pub fn test_find() {
+        let long = LONG.as_bytes();
+        for needle in *b"\n!qz#" {
+            assert_eq!(find_byte(long, needle), scalar::find_byte(long, needle));
+            assert_eq!(count_byte(long, needle), scalar::count_byte(long, needle));
+        }
+
+        assert_eq!(find_digit(long), Some(45));
+        assert_eq!(find_non_digit(&long[45..]), Some(4));
+        assert_eq!(find_digit(b"no digits"), None);
+        assert_eq!(count_byte(LONG.repeat(3).as_bytes(), b'o'), 15);
+    }
+
+    #[test]
+    pub fn test_split() {
+        let parts: Vec<_> = split(b"a,b,,c", b',').collect();
+        assert_eq!(parts, [&b"a"[..], b"b", b"", b"c"]);
+
+        let long = LONG.repeat(2);
+        let ours: Vec<_> = split(long.as_bytes(), b' ').collect();
+        let std: Vec<_> = long.as_bytes().split(|&b| b == b' ').collect();
+        assert_eq!(ours, std);
+    }
+
+    #[test]
+    pub fn test_lines() {
+        for input in ["", "\n", "a\n\nb", "a\r\nb\n", "å\nä\nö\n", &LONG.repeat(3)] {
+            let ours: Vec<_> = lines(input).collect();
+            let std: Vec<_> = input.lines().collect();
+            assert_eq!(ours, std, "{input:?}");
+        }
+    }
+
+    #[test]
+    pub fn test_digit_runs() {
+        let runs: Vec<_> = digit_runs(b"12 abc 3-45").collect();
+        assert_eq!(runs, [0..2, 7..8, 9..11]);
+
+        let long = LONG.as_bytes();
+        let runs: Vec<_> = digit_runs(long).map(|r| &LONG[r]).collect();
+        assert_eq!(runs, ["1234", "5678"]);
+
+        // runs crossing a block boundary, and ending at the end of the input
+        let digits = format!("ab{}", "9".repeat(70));
+        let runs: Vec<_> = digit_runs(digits.as_bytes()).collect();
+        assert_eq!(runs, vec![2..72]);
+
+        let spaced = "1 ".repeat(40);
+        let scalar_runs = spaced.match_indices('1').map(|(i, _)| i..i + 1);
+        assert!(digit_runs(spaced.as_bytes()).eq(scalar_runs));
+    }
+
+    fn bench_inputs() -> [&'static str; 3] {
+        [
+            include_str!("../day01/test-input2"),
+            include_str!("../day04/test-input"),
+            include_str!("../day09/test-input"),
+        ]
+    }
+
+    #[bench]
+    fn bench_lines_simd(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                lines(black_box(input)).for_each(|line| {
+                    black_box(line);
+                });
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_lines_str(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                black_box(input).lines().for_each(|line| {
+                    black_box(line);
+                });
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_find_byte_simd(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                black_box(find_byte(black_box(input.as_bytes()), b'|'));
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_find_byte_scalar(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                black_box(scalar::find_byte(black_box(input.as_bytes()), b'|'));
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_digit_runs_simd(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                digit_runs(black_box(input.as_bytes())).for_each(|run| {
+                    black_box(run);
+                });
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_digit_runs_scalar(b: &mut Bencher) {
+        let inputs = bench_inputs();
+        b.iter(|| {
+            for input in inputs {
+                let bytes = black_box(input.as_bytes());
+                let mut pos = 0;
+                while let Some(start) = scalar::find_digit(&bytes[pos..]) {
+                    let start = pos + start;
+                    pos =
+                        scalar::find_non_digit(&bytes[start..]).map_or(bytes.len(), |i| start + i);
+                    black_box(start..pos);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,252 @@
+//! A sparse 2D grid, for puzzles where only a few cells of a huge (or unbounded) map matter
+
+use super::grid::{Grid, SignedPos};
+use super::HashMap;
+use std::ops::{Index, RangeInclusive};
+
+/// An inclusive bounding box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    /// The top left corner.
+    pub min: SignedPos,
+
+    /// The bottom right corner. Note that this is inclusive.
+    pub max: SignedPos,
+}
+
+impl Bounds {
+    /// Create a bounding box containing a single position.
+    pub fn point(pos: SignedPos) -> Self {
+        Bounds { min: pos, max: pos }
+    }
+
+    /// Grow the bounding box to include `pos`.
+    pub fn extend(&mut self, (x, y): SignedPos) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+
+    pub fn contains(&self, (x, y): SignedPos) -> bool {
+        self.xs().contains(&x) && self.ys().contains(&y)
+    }
+
+    /// Check if `pos` is on the edge of the bounding box.
+    pub fn on_edge(&self, (x, y): SignedPos) -> bool {
+        x == self.min.0 || x == self.max.0 || y == self.min.1 || y == self.max.1
+    }
+
+    pub fn xs(&self) -> RangeInclusive<i64> {
+        self.min.0..=self.max.0
+    }
+
+    pub fn ys(&self) -> RangeInclusive<i64> {
+        self.min.1..=self.max.1
+    }
+
+    pub fn width(&self) -> u64 {
+        self.max.0.abs_diff(self.min.0) + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        self.max.1.abs_diff(self.min.1) + 1
+    }
+}
+
+/// A grid which only stores the cells that have been inserted, and keeps track of their bounding
+/// box
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<SignedPos, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a grid from lines of text, keeping the cells for which `f` returns `Some`.
+    ///
+    /// Position `(0, 0)` is the first char of the input.
+    pub fn parse(input: &str, mut f: impl FnMut(u8) -> Option<T>) -> Self {
+        let mut grid = SparseGrid::new();
+        for (y, line) in (0..).zip(input.lines()) {
+            for (x, b) in (0..).zip(line.bytes()) {
+                if let Some(value) = f(b) {
+                    grid.insert((x, y), value);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Insert a cell, and return the previous value at that position.
+    pub fn insert(&mut self, pos: SignedPos, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(pos),
+            None => self.bounds = Some(Bounds::point(pos)),
+        }
+        self.cells.insert(pos, value)
+    }
+
+    /// Remove a cell, and shrink the bounding box if needed.
+    ///
+    /// Shrinking the bounding box means looking at every cell, so this is slow for cells on the
+    /// edge.
+    pub fn remove(&mut self, pos: SignedPos) -> Option<T> {
+        let value = self.cells.remove(&pos)?;
+        if self.bounds.is_some_and(|bounds| bounds.on_edge(pos)) {
+            self.bounds = self.recompute_bounds();
+        }
+        Some(value)
+    }
+
+    fn recompute_bounds(&self) -> Option<Bounds> {
+        let mut positions = self.cells.keys().copied();
+        let mut bounds = Bounds::point(positions.next()?);
+        positions.for_each(|pos| bounds.extend(pos));
+        Some(bounds)
+    }
+
+    pub fn get(&self, pos: SignedPos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: SignedPos) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    pub fn contains(&self, pos: SignedPos) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    /// The smallest box containing all cells, or `None` if the grid is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterate over all cells, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (SignedPos, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    /// Iterate over the positions of all cells, in no particular order.
+    pub fn positions(&self) -> impl Iterator<Item = SignedPos> + '_ {
+        self.cells.keys().copied()
+    }
+
+    /// Convert to a dense [Grid] covering the bounding box, with `empty` in the missing cells.
+    ///
+    /// Position `(0, 0)` in the dense grid corresponds to `bounds().min`.
+    pub fn to_dense(&self, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let Some(bounds) = self.bounds else {
+            return Grid::new(0, 0, vec![]);
+        };
+
+        let cells = bounds
+            .ys()
+            .flat_map(|y| bounds.xs().map(move |x| (x, y)))
+            .map(|pos| self.get(pos).unwrap_or(&empty).clone())
+            .collect();
+
+        let size = |n: u64| usize::try_from(n).expect("grid is too large");
+        Grid::new(size(bounds.width()), size(bounds.height()), cells)
+    }
+}
+
+impl<T> Index<SignedPos> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: SignedPos) -> &T {
+        self.get(pos).expect("no cell at grid position")
+    }
+}
+
+impl<T> Extend<(SignedPos, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (SignedPos, T)>>(&mut self, iter: I) {
+        for (pos, value) in iter {
+            self.insert(pos, value);
+        }
+    }
+}
+
+impl<T> FromIterator<(SignedPos, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (SignedPos, T)>>(iter: I) -> Self {
+        let mut grid = SparseGrid::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> IntoIterator for SparseGrid<T> {
+    type Item = (SignedPos, T);
+    type IntoIter = std::collections::hash_map::IntoIter<SignedPos, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cells.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_bounds() {
+        let mut grid = SparseGrid::parse("..#\n#..\n", |b| (b == b'#').then_some(b));
+        assert_eq!(grid.len(), 2);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds {
+                min: (0, 0),
+                max: (2, 1)
+            })
+        );
+
+        grid.insert((-1_000_000_000_000, 5), b'x');
+        let bounds = grid.bounds().unwrap();
+        assert_eq!(bounds.min, (-1_000_000_000_000, 0));
+        assert_eq!((bounds.width(), bounds.height()), (1_000_000_000_003, 6));
+        assert!(bounds.contains((-5, 3)));
+        assert!(!bounds.contains((3, 3)));
+
+        assert_eq!(grid.remove((-1_000_000_000_000, 5)), Some(b'x'));
+        assert_eq!(grid.bounds().unwrap().min, (0, 0));
+
+        grid.remove((2, 0));
+        grid.remove((0, 1));
+        assert_eq!(grid.bounds(), None);
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    pub fn test_to_dense() {
+        let grid: SparseGrid<u8> = [((-1, 3), b'a'), ((1, 4), b'b')].into_iter().collect();
+        assert_eq!(grid[(1, 4)], b'b');
+        assert_eq!(grid.get((0, 4)), None);
+
+        let dense = grid.to_dense(b'.');
+        assert_eq!(dense, Grid::parse("a..\n..b\n", |b| b));
+    }
+}
@@ -0,0 +1,122 @@
+//! Disjoint-set forest, for keeping track of connected components
+
+/// A union-find over the elements `0..len`
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    /// The parent of each element. Roots are their own parents.
+    parent: Vec<usize>,
+
+    /// Size of the component, only valid for roots.
+    size: Vec<usize>,
+
+    /// Number of disjoint components.
+    components: usize,
+}
+
+impl UnionFind {
+    /// Create a union-find where every element is its own component.
+    pub fn new(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Find the representative element of the component containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // path compression: point everything we passed directly at the root
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+
+        root
+    }
+
+    /// Merge the components containing `a` and `b`. Returns false if they were already connected.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        // union by size: attach the smaller tree to the larger one
+        let (small, large) = if self.size[a] < self.size[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+        self.components -= 1;
+
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of disjoint components.
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Size of the component containing `x`.
+    pub fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Iterate over all components, ordered by their smallest element.
+    pub fn groups(&mut self) -> impl Iterator<Item = Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![vec![]; self.len()];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            groups[root].push(x);
+        }
+
+        groups.sort_by_key(|group| group.first().copied().unwrap_or(usize::MAX));
+        groups.into_iter().filter(|group| !group.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_union_find() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.component_count(), 6);
+
+        assert!(uf.union(0, 1));
+        assert!(uf.union(4, 1));
+        assert!(uf.union(2, 3));
+        assert!(!uf.union(0, 4));
+
+        assert_eq!(uf.component_count(), 3);
+        assert_eq!(uf.component_size(4), 3);
+        assert_eq!(uf.component_size(3), 2);
+        assert!(uf.connected(0, 4));
+        assert!(!uf.connected(0, 5));
+
+        let groups: Vec<_> = uf.groups().collect();
+        assert_eq!(groups, [vec![0, 1, 4], vec![2, 3], vec![5]]);
+    }
+}